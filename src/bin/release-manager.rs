@@ -69,9 +69,9 @@ fn main() {
     };
 
     let code: i32 = match matches.subcommand() {
-        ("local", Some(sub_m)) => process_project_command(sub_m, &config),
+        ("local", Some(sub_m)) => process_project_command(sub_m, &config, &project_root),
         ("github", Some(sub_m)) => process_github_command(sub_m, &config, &project_root),
-        ("artifactory", Some(sub_m)) => process_artifactory_command(sub_m, &config),
+        ("artifactory", Some(sub_m)) => process_artifactory_command(sub_m, &config, &project_root),
         _ => {
             error!("No command avaliable");
             -1
@@ -0,0 +1,317 @@
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use chrono::Utc;
+use semver::Version;
+
+use commits::{parse_commit, ConventionalCommit};
+use config::Config;
+use errors::{CommandError, ErrorCodes};
+use file::read_file_to_string;
+use git;
+
+const CHANGELOG_NAME: &'static str = "CHANGELOG.md";
+const CHANGELOG_HEADER: &'static str = "# Changelog\n\nAll notable changes to this project will be documented in this file.\n";
+
+/// Keep a Changelog headings, in the order they are written, with the commit types that
+/// go under each. Breaking changes always go under Changed.
+const SECTIONS: &'static [(&str, &[&str])] = &[
+    ("Added", &["feat"]),
+    ("Changed", &["perf", "refactor"]),
+    ("Deprecated", &["deprecate"]),
+    ("Removed", &["remove", "revert"]),
+    ("Fixed", &["fix"]),
+    ("Security", &["security"]),
+];
+
+/// The `## [Unreleased]` section of the changelog, moved under the version being released.
+pub(crate) struct PromotedChangelog {
+    /// Path of the changelog relative to the project root.
+    pub(crate) path: String,
+    pub(crate) text: String,
+    pub(crate) notes: String,
+}
+
+/// Where the changelog lives relative to the project root, `CHANGELOG.md` unless `path`
+/// is set in [changelog].
+fn changelog_file_name(config: &Config) -> String {
+    return config
+        .changelog
+        .as_ref()
+        .and_then(|x| x.path.clone())
+        .unwrap_or(s!(CHANGELOG_NAME));
+}
+
+pub(crate) fn changelog_path(config: &Config, project_root: &Path) -> PathBuf {
+    return project_root.join(changelog_file_name(config));
+}
+
+/// Renames the `## [Unreleased]` section to `version`, leaving a new empty Unreleased
+/// section above it. Returns `None` when there is no changelog or nothing is unreleased.
+pub(crate) fn promote_unreleased(config: &Config, project_root: &Path, version: &Version) -> Option<PromotedChangelog> {
+    let path = changelog_path(config, project_root);
+    if !path.exists() {
+        return None;
+    }
+
+    let (text, notes) = promote_section(&read_file_to_string(&path), version, &today())?;
+    debug!("Promoting the unreleased changes in {:?} to {}", path, version);
+
+    return Some(PromotedChangelog {
+        path: changelog_file_name(config),
+        text: text,
+        notes: notes,
+    });
+}
+
+/// The notes for `version`, built from the commits since the previous version tag. Returns
+/// `None` when none of the commits belong in a changelog.
+pub(crate) fn render_release_notes(project_root: &Path, version: &Version) -> Result<Option<String>, CommandError> {
+    let tags = match git::find_version_tags(project_root.to_path_buf()) {
+        Ok(tags) => tags,
+        Err(err) => return Err(CommandError::new(err, "Unable to read version tags")),
+    };
+
+    let since = tags.into_iter().filter(|x| x.version < *version).last().map(|x| x.commit);
+    let commits = match git::find_commits_since(project_root.to_path_buf(), since) {
+        Ok(commits) => commits,
+        Err(err) => return Err(CommandError::new(err, "Unable to read commits")),
+    };
+
+    let parsed: Vec<(String, ConventionalCommit)> = commits
+        .iter()
+        .filter_map(|x| parse_commit(&x.message).map(|parsed| (s!(&x.sha[..7]), parsed)))
+        .collect();
+
+    return Ok(render_notes(&parsed));
+}
+
+/// A `## [version] - date` section, ready to go into CHANGELOG.md.
+pub(crate) fn render_section(version: &Version, notes: &str) -> String {
+    return format!("## [{}] - {}\n\n{}", version, today(), notes);
+}
+
+fn today() -> String {
+    return Utc::now().format("%Y-%m-%d").to_string();
+}
+
+fn is_unreleased_heading(line: &str) -> bool {
+    return line.to_lowercase().starts_with("## [unreleased]");
+}
+
+/// Link reference definitions, like the compare links at the bottom of the file.
+fn is_link_definition(line: &str) -> bool {
+    return line.starts_with('[') && line.contains("]: ");
+}
+
+fn promote_section(changelog: &str, version: &Version, date: &str) -> Option<(String, String)> {
+    let mut offset = 0;
+    let mut heading: Option<(usize, usize)> = None;
+    let mut body_end = changelog.len();
+
+    for line in changelog.split('\n') {
+        let line_start = offset;
+        offset = (offset + line.len() + 1).min(changelog.len());
+
+        match heading {
+            None if is_unreleased_heading(line) => heading = Some((line_start, offset)),
+            Some(_) if line.starts_with("## ") || is_link_definition(line) => {
+                body_end = line_start;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let (heading_start, body_start) = heading?;
+    let notes = changelog[body_start..body_end].trim();
+    if notes.is_empty() {
+        return None;
+    }
+
+    let promoted = format!(
+        "{}## [Unreleased]\n\n## [{}] - {}\n\n{}\n\n{}",
+        &changelog[..heading_start],
+        version,
+        date,
+        notes,
+        &changelog[body_end..]
+    );
+
+    return Some((update_compare_links(&promoted, version), format!("{}\n", notes)));
+}
+
+/// Points the Unreleased compare link at the new tag and adds a link for the version,
+/// comparing it with the tag Unreleased used to start from.
+fn update_compare_links(changelog: &str, version: &Version) -> String {
+    let lines: Vec<String> = changelog
+        .split('\n')
+        .map(|line| {
+            let url = match line.find("]: ") {
+                Some(index) if line.to_lowercase().starts_with("[unreleased]") => &line[index + 3..],
+                _ => return s!(line),
+            };
+
+            let (base, previous) = match url.find("/compare/") {
+                Some(index) if url.ends_with("...HEAD") => (&url[..index], &url[index + 9..url.len() - 7]),
+                _ => return s!(line),
+            };
+
+            format!(
+                "[Unreleased]: {base}/compare/v{version}...HEAD\n[{version}]: {base}/compare/{previous}...v{version}",
+                base = base,
+                version = version,
+                previous = previous
+            )
+        })
+        .collect();
+
+    return lines.join("\n");
+}
+
+fn render_notes(commits: &[(String, ConventionalCommit)]) -> Option<String> {
+    let mut notes = String::new();
+
+    for &(heading, kinds) in SECTIONS {
+        let entries: Vec<String> = commits
+            .iter()
+            .filter(|&&(_, ref commit)| {
+                if commit.breaking {
+                    heading == "Changed"
+                } else {
+                    kinds.contains(&commit.kind.as_str())
+                }
+            })
+            .map(|&(ref sha, ref commit)| render_entry(sha, commit))
+            .collect();
+
+        if entries.is_empty() {
+            continue;
+        }
+
+        notes.push_str(&format!("### {}\n\n{}\n\n", heading, entries.join("\n")));
+    }
+
+    if notes.is_empty() {
+        return None;
+    }
+
+    return Some(notes);
+}
+
+fn render_entry(sha: &str, commit: &ConventionalCommit) -> String {
+    let mut entry = s!("- ");
+    if commit.breaking {
+        entry.push_str("**BREAKING** ");
+    }
+
+    if let Some(ref scope) = commit.scope {
+        entry.push_str(&format!("**{}:** ", scope));
+    }
+
+    entry.push_str(&format!("{} ({})", commit.description, sha));
+    return entry;
+}
+
+/// Whether the changelog covers `version`, either with its own section or with changes
+/// waiting under Unreleased that will be promoted when it's released.
+pub(crate) fn has_entry(changelog: &str, version: &Version) -> bool {
+    let heading = format!("## [{}]", version);
+    if changelog.lines().any(|x| x.starts_with(&heading)) {
+        return true;
+    }
+
+    return promote_section(changelog, version, &today()).is_some();
+}
+
+/// Adds `section` above the newest release, keeping the title and any `## [Unreleased]`
+/// section at the top of the file.
+pub(crate) fn prepend_section(changelog: &str, version: &Version, section: &str) -> Result<String, CommandError> {
+    if changelog.lines().any(|x| x.starts_with(&format!("## [{}]", version))) {
+        return Err(CommandError::new(
+            ErrorCodes::ChangelogEntryExists,
+            format!("The changelog already has a section for {}", version),
+        ));
+    }
+
+    let changelog = if changelog.trim().is_empty() {
+        format!("{}\n", CHANGELOG_HEADER)
+    } else {
+        s!(changelog)
+    };
+
+    let mut offset = 0;
+    for line in changelog.split('\n') {
+        if line.starts_with("## ") && !line.to_lowercase().starts_with("## [unreleased]") {
+            return Ok(format!("{}{}{}", &changelog[..offset], section, &changelog[offset..]));
+        }
+        offset += line.len() + 1;
+    }
+
+    let separator = if changelog.ends_with("\n\n") {
+        ""
+    } else if changelog.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+
+    return Ok(format!("{}{}{}", changelog, separator, section.trim_right()) + "\n");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_group_commits() {
+        let commits: Vec<(String, ConventionalCommit)> = vec![
+            "feat(api): add releases endpoint",
+            "fix: handle empty tags",
+            "chore: update deps",
+            "feat!: drop the old config",
+        ].iter()
+            .map(|x| (s!("abc1234"), parse_commit(x).unwrap()))
+            .collect();
+
+        assert_eq!(
+            render_notes(&commits).unwrap(),
+            "### Added\n\n- **api:** add releases endpoint (abc1234)\n\n\
+             ### Changed\n\n- **BREAKING** drop the old config (abc1234)\n\n\
+             ### Fixed\n\n- handle empty tags (abc1234)\n\n"
+        );
+    }
+
+    #[test]
+    fn will_promote_unreleased_section() {
+        let version = Version::parse("1.1.0").unwrap();
+        let changelog = "# Changelog\n\n## [Unreleased]\n### Fixed\n- a bug\n\n## [1.0.0] - 2018-01-01\n\n- first\n\n\
+                         [Unreleased]: https://github.com/foo/bar/compare/v1.0.0...HEAD\n\
+                         [1.0.0]: https://github.com/foo/bar/compare/v0.9.0...v1.0.0\n";
+
+        let (text, notes) = promote_section(changelog, &version, "2018-02-01").unwrap();
+        assert_eq!(notes, "### Fixed\n- a bug\n");
+        assert_eq!(
+            text,
+            "# Changelog\n\n## [Unreleased]\n\n## [1.1.0] - 2018-02-01\n\n### Fixed\n- a bug\n\n## [1.0.0] - 2018-01-01\n\n- first\n\n\
+             [Unreleased]: https://github.com/foo/bar/compare/v1.1.0...HEAD\n\
+             [1.1.0]: https://github.com/foo/bar/compare/v1.0.0...v1.1.0\n\
+             [1.0.0]: https://github.com/foo/bar/compare/v0.9.0...v1.0.0\n"
+        );
+
+        assert!(promote_section("# Changelog\n\n## [Unreleased]\n\n## [1.0.0]\n", &version, "2018-02-01").is_none());
+    }
+
+    #[test]
+    fn will_prepend_below_unreleased() {
+        let version = Version::parse("1.1.0").unwrap();
+        let changelog = "# Changelog\n\n## [Unreleased]\n\n## [1.0.0] - 2018-01-01\n\n- first\n";
+        let section = "## [1.1.0] - 2018-02-01\n\n### Fixed\n\n- a bug (abc1234)\n\n";
+
+        assert_eq!(
+            prepend_section(changelog, &version, section).ok().unwrap(),
+            "# Changelog\n\n## [Unreleased]\n\n## [1.1.0] - 2018-02-01\n\n### Fixed\n\n- a bug (abc1234)\n\n## [1.0.0] - 2018-01-01\n\n- first\n"
+        );
+        assert!(prepend_section(changelog, &Version::parse("1.0.0").unwrap(), section).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use std::path::Path;
+use std::vec::Vec;
+
+use json::JsonValue;
+use semver::Version;
+
+use changelog::{changelog_path, has_entry};
+use config::{CheckConfig, Config};
+use errors::ErrorCodes;
+use file::read_file_to_string;
+use git;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl CheckStatus {
+    pub(crate) fn name(&self) -> &'static str {
+        return match *self {
+            CheckStatus::Passed => "passed",
+            CheckStatus::Failed => "failed",
+            CheckStatus::Skipped => "skipped",
+        };
+    }
+}
+
+pub(crate) struct CheckResult {
+    pub(crate) name: &'static str,
+    pub(crate) status: CheckStatus,
+    pub(crate) message: String,
+}
+
+impl CheckResult {
+    fn new<S: Into<String>>(name: &'static str, status: CheckStatus, message: S) -> Self {
+        return CheckResult {
+            name: name,
+            status: status,
+            message: message.into(),
+        };
+    }
+}
+
+/// Runs every rule in [check]. `clean-tree`, `version-increased` and `tag-available` are on
+/// unless turned off, `changelog` has to be turned on, and the branch is only checked when
+/// `release-branches` is set.
+pub(crate) fn run_checks(config: &Config, project_root: &Path, version: &Version) -> Vec<CheckResult> {
+    let check_config: CheckConfig = config.check.clone().unwrap_or_default();
+
+    let mut results = Vec::new();
+
+    results.push(if check_config.clean_tree.unwrap_or(true) {
+        check_clean_tree(project_root)
+    } else {
+        CheckResult::new("clean-tree", CheckStatus::Skipped, "Turned off in the config")
+    });
+
+    results.push(match check_config.release_branches {
+        Some(ref branches) => check_release_branch(project_root, branches),
+        None => CheckResult::new("release-branch", CheckStatus::Skipped, "No release-branches in the config"),
+    });
+
+    results.push(if check_config.version_increased.unwrap_or(true) {
+        check_version_increased(project_root, version)
+    } else {
+        CheckResult::new("version-increased", CheckStatus::Skipped, "Turned off in the config")
+    });
+
+    results.push(if check_config.tag_available.unwrap_or(true) {
+        check_tag_available(project_root, version)
+    } else {
+        CheckResult::new("tag-available", CheckStatus::Skipped, "Turned off in the config")
+    });
+
+    results.push(if check_config.changelog.unwrap_or(false) {
+        check_changelog(config, project_root, version)
+    } else {
+        CheckResult::new("changelog", CheckStatus::Skipped, "Turned off in the config")
+    });
+
+    return results;
+}
+
+fn check_clean_tree(project_root: &Path) -> CheckResult {
+    return match git::find_dirty_files(project_root.to_path_buf()) {
+        Ok(ref files) if files.is_empty() => CheckResult::new("clean-tree", CheckStatus::Passed, "Working tree is clean"),
+        Ok(files) => CheckResult::new(
+            "clean-tree",
+            CheckStatus::Failed,
+            format!("Working tree has uncommitted changes: {}", files.join(", ")),
+        ),
+        Err(err) => CheckResult::new(
+            "clean-tree",
+            CheckStatus::Failed,
+            format!("Unable to read the working tree: {:?}", err),
+        ),
+    };
+}
+
+/// Passes when any branch at HEAD is a release branch, as after a fast-forward merge the
+/// feature branch and the release branch both point at it. The checked out branch is
+/// preferred when HEAD isn't detached.
+fn check_release_branch(project_root: &Path, branches: &[String]) -> CheckResult {
+    let current = match git::find_current_branch(project_root.to_path_buf()) {
+        Ok(current) => current,
+        Err(err) => return branch_error(err),
+    };
+
+    let at_head = match git::find_last_commit(project_root.to_path_buf())
+        .and_then(|head| git::find_branches_for_commit(project_root.to_path_buf(), head))
+    {
+        Ok(at_head) => at_head,
+        Err(err) => return branch_error(err),
+    };
+
+    return release_branch_result(current, at_head, branches);
+}
+
+fn branch_error(err: ErrorCodes) -> CheckResult {
+    return CheckResult::new(
+        "release-branch",
+        CheckStatus::Failed,
+        format!("Unable to find the branch for HEAD: {:?}", err),
+    );
+}
+
+fn release_branch_result(current: Option<String>, at_head: Vec<String>, branches: &[String]) -> CheckResult {
+    let mut candidates: Vec<String> = current.into_iter().collect();
+    for branch in at_head {
+        if !candidates.contains(&branch) {
+            candidates.push(branch);
+        }
+    }
+
+    if let Some(branch) = candidates.iter().find(|x| branches.contains(x)) {
+        return CheckResult::new(
+            "release-branch",
+            CheckStatus::Passed,
+            format!("HEAD is on release branch {}", branch),
+        );
+    }
+
+    if candidates.is_empty() {
+        return CheckResult::new(
+            "release-branch",
+            CheckStatus::Failed,
+            format!("HEAD is not on a branch, releases come from {}", branches.join(", ")),
+        );
+    }
+
+    return CheckResult::new(
+        "release-branch",
+        CheckStatus::Failed,
+        format!("HEAD is on {}, releases come from {}", candidates.join(", "), branches.join(", ")),
+    );
+}
+
+fn check_version_increased(project_root: &Path, version: &Version) -> CheckResult {
+    return match git::find_latest_version_tag(project_root.to_path_buf()) {
+        Ok(Some(ref tag)) if *version > tag.version => CheckResult::new(
+            "version-increased",
+            CheckStatus::Passed,
+            format!("{} is after the latest tag {}", version, tag.name),
+        ),
+        Ok(Some(tag)) => CheckResult::new(
+            "version-increased",
+            CheckStatus::Failed,
+            format!("{} is not after the latest tag {}", version, tag.name),
+        ),
+        Ok(None) => CheckResult::new("version-increased", CheckStatus::Passed, "No version tags yet"),
+        Err(err) => CheckResult::new(
+            "version-increased",
+            CheckStatus::Failed,
+            format!("Unable to read version tags: {:?}", err),
+        ),
+    };
+}
+
+fn check_tag_available(project_root: &Path, version: &Version) -> CheckResult {
+    let tag_name = format!("v{}", version);
+
+    return match git::tag_exists(project_root.to_path_buf(), &tag_name) {
+        Ok(false) => CheckResult::new("tag-available", CheckStatus::Passed, format!("{} does not exist yet", tag_name)),
+        Ok(true) => CheckResult::new("tag-available", CheckStatus::Failed, format!("{} already exists", tag_name)),
+        Err(err) => CheckResult::new(
+            "tag-available",
+            CheckStatus::Failed,
+            format!("Unable to read tags: {:?}", err),
+        ),
+    };
+}
+
+fn check_changelog(config: &Config, project_root: &Path, version: &Version) -> CheckResult {
+    let path = changelog_path(config, project_root);
+    if !path.exists() {
+        return CheckResult::new("changelog", CheckStatus::Failed, format!("{} does not exist", path.display()));
+    }
+
+    return if has_entry(&read_file_to_string(&path), version) {
+        CheckResult::new("changelog", CheckStatus::Passed, format!("{} has an entry for {}", path.display(), version))
+    } else {
+        CheckResult::new(
+            "changelog",
+            CheckStatus::Failed,
+            format!("{} has no entry for {} and nothing under Unreleased", path.display(), version),
+        )
+    };
+}
+
+pub(crate) fn report_json(version: &Version, results: &[CheckResult]) -> JsonValue {
+    let checks: Vec<JsonValue> = results
+        .iter()
+        .map(|x| {
+            object!{
+                "name" => x.name,
+                "status" => x.status.name(),
+                "message" => x.message.clone()
+            }
+        })
+        .collect();
+
+    return object!{
+        "version" => version.to_string(),
+        "passed" => results.iter().all(|x| x.status != CheckStatus::Failed),
+        "checks" => checks
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use toml;
+
+    fn config(check: &str) -> Config {
+        let text = format!(
+            "[github]\nowner = \"foo\"\nrepo = \"bar\"\n\n[changelog]\npath = \"tests/fixtures/CHANGELOG.md\"\n\n[check]\n{}",
+            check
+        );
+        return toml::from_str(&text).unwrap();
+    }
+
+    fn statuses(results: &[CheckResult]) -> Vec<(&'static str, CheckStatus)> {
+        return results.iter().map(|x| (x.name, x.status)).collect();
+    }
+
+    #[test]
+    fn will_skip_checks_that_are_turned_off() {
+        let config = config("clean-tree = false\nversion-increased = false\ntag-available = false\n");
+        let results = run_checks(&config, Path::new(env!("CARGO_MANIFEST_DIR")), &Version::parse("1.0.0").unwrap());
+
+        assert_eq!(
+            statuses(&results),
+            vec![
+                ("clean-tree", CheckStatus::Skipped),
+                ("release-branch", CheckStatus::Skipped),
+                ("version-increased", CheckStatus::Skipped),
+                ("tag-available", CheckStatus::Skipped),
+                ("changelog", CheckStatus::Skipped),
+            ]
+        );
+    }
+
+    #[test]
+    fn will_check_changelog_has_entry() {
+        let config = config("clean-tree = false\nversion-increased = false\ntag-available = false\nchangelog = true\n");
+        let project_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        let results = run_checks(&config, project_root, &Version::parse("1.0.0").unwrap());
+        assert_eq!(results[4].status, CheckStatus::Passed);
+
+        let results = run_checks(&config, project_root, &Version::parse("1.1.0").unwrap());
+        assert_eq!(results[4].status, CheckStatus::Failed);
+    }
+
+    #[test]
+    fn will_pass_when_any_branch_at_head_is_a_release_branch() {
+        let branches = vec![s!("master")];
+
+        let result = release_branch_result(Some(s!("feature-x")), vec![s!("feature-x"), s!("master")], &branches);
+        assert_eq!(result.status, CheckStatus::Passed);
+        assert_eq!(result.message, "HEAD is on release branch master");
+
+        let result = release_branch_result(None, vec![s!("feature-x")], &branches);
+        assert_eq!(result.status, CheckStatus::Failed);
+    }
+
+    #[test]
+    fn will_match_release_branches_with_slashes() {
+        let branches = vec![s!("release/1.x")];
+
+        let result = release_branch_result(None, vec![s!("release/1.x")], &branches);
+        assert_eq!(result.status, CheckStatus::Passed);
+
+        let result = release_branch_result(None, vec![s!("1.x")], &branches);
+        assert_eq!(result.status, CheckStatus::Failed);
+    }
+
+    #[test]
+    fn will_report_json() {
+        let version = Version::parse("1.0.0").unwrap();
+        let mut results = vec![
+            CheckResult::new("clean-tree", CheckStatus::Passed, "Working tree is clean"),
+            CheckResult::new("changelog", CheckStatus::Skipped, "Turned off in the config"),
+        ];
+
+        let report = report_json(&version, &results);
+        assert_eq!(report["version"], "1.0.0");
+        assert_eq!(report["passed"], true);
+        assert_eq!(report["checks"][0]["name"], "clean-tree");
+        assert_eq!(report["checks"][1]["status"], "skipped");
+        assert_eq!(report["checks"][1]["message"], "Turned off in the config");
+
+        results.push(CheckResult::new("tag-available", CheckStatus::Failed, "v1.0.0 already exists"));
+        assert_eq!(report_json(&version, &results)["passed"], false);
+    }
+}
@@ -77,9 +77,9 @@ pub fn artifactory_clap<'a, 'b>() -> App<'a, 'b> {
         .subcommand(distribute);
 }
 
-pub fn process_artifactory_command(args: &ArgMatches, config: &Config) -> i32 {
+pub fn process_artifactory_command(args: &ArgMatches, config: &Config, project_root: &Path) -> i32 {
     let response = match args.subcommand() {
-        ("publish", Some(m)) => upload_artifacts(m, config),
+        ("publish", Some(m)) => upload_artifacts(m, config, project_root),
         ("distribute", Some(m)) => distribute_artifacts(m, config),
         _ => Err(CommandError::new(
             ErrorCodes::Unknown,
@@ -96,7 +96,7 @@ pub fn process_artifactory_command(args: &ArgMatches, config: &Config) -> i32 {
     };
 }
 
-fn upload_artifacts(args: &ArgMatches, config: &Config) -> Result<(), CommandError> {
+fn upload_artifacts(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
     let repo_path = Path::new(args.value_of("REPO").unwrap());
     if !Path::exists(repo_path) && repo_path.is_dir() {
         trace!("Path `{:?}` does not exists or is not directory", repo_path);
@@ -114,7 +114,7 @@ fn upload_artifacts(args: &ArgMatches, config: &Config) -> Result<(), CommandErr
 
     let version = match args.value_of("version") {
         Some(x) => x.to_string(),
-        None => build_project(None).unwrap().get_version().to_string().clone()
+        None => build_project(config, project_root)?.get_version().to_string().clone()
     };
 
     let build_number: i32 = args.value_of("build-number").unwrap().parse::<i32>().unwrap();
@@ -1,6 +1,7 @@
 use std::boxed::Box;
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::str;
 use std::vec::Vec;
 
 use clap::ArgMatches;
@@ -27,6 +28,30 @@ pub(crate) struct GitHubImpl {
     requester: Box<HttpRequester>,
 }
 
+/// A file to attach to a release, with the label GitHub shows instead of the file name.
+pub struct ReleaseArtifact {
+    pub path: PathBuf,
+    pub label: Option<String>,
+}
+
+/// What to do when the release already has an asset with the same name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExistingAssets {
+    Fail,
+    /// Delete the attached asset and upload the file again.
+    Overwrite,
+    /// Leave the attached asset alone when it's the same size as the file.
+    Skip,
+}
+
+/// How a new release is shown on GitHub.
+pub struct ReleaseOptions {
+    pub draft: bool,
+    pub prerelease: bool,
+    /// GitHub's `make_latest`, one of `true`, `false` or `legacy`. Left to GitHub when unset.
+    pub make_latest: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum GitHubError {
     FilesDoesNotExist(Vec<String>),
@@ -38,6 +63,12 @@ pub enum GitHubError {
     UnableToMakeURI,
     UnableToUpdateReference,
     UnableToUploadArtifact,
+    UnableToDeleteAsset(String),
+    /// The release's tag points at `existing`, not the commit being released.
+    ReleaseConflict { tag: String, existing: String, requested: String },
+    MissingAssets(Vec<String>),
+    AssetsAlreadyExist(Vec<String>),
+    AssetSizeDiffers(String),
 }
 
 pub trait GitHub {
@@ -46,19 +77,22 @@ pub trait GitHub {
         id: String,
         version: Version,
         body: String,
-        draft: bool,
+        options: ReleaseOptions,
     ) -> Result<(), GitHubError>;
     fn update_files(
         &self,
         head: String,
         branch_name: String,
         files: HashMap<String, String>,
+        message: String,
     ) -> Result<(), GitHubError>;
     fn add_artifacts_to_release(
         &self,
         release_name: String,
-        artifacts: BTreeMap<String, PathBuf>,
+        artifacts: BTreeMap<String, ReleaseArtifact>,
+        existing_assets: ExistingAssets,
     ) -> Result<(), GitHubError>;
+    fn publish_release(&self, release_name: String, expected_assets: Vec<String>) -> Result<(), GitHubError>;
 }
 
 impl GitHubImpl {
@@ -148,10 +182,118 @@ impl GitHubImpl {
         };
     }
 
-    fn validate_files(artifacts: &BTreeMap<String, PathBuf>) -> Result<(), GitHubError> {
+    /// The release for `tag`, or `None` when GitHub doesn't have one. Drafts aren't returned
+    /// by `releases/tags`, so every page of releases is searched when it comes up empty.
+    fn find_release_by_tag(&self, tag: &str) -> Result<Option<JsonValue>, GitHubError> {
+        let uri = self.build_base_url(vec!["releases", "tags", tag])?;
+        match self.handle_network_request_without_body(uri) {
+            Ok(release) => return Ok(Some(release)),
+            Err(GitHubError::UnableToCreateRelease(StatusCode::NotFound)) => {}
+            Err(err) => return Err(err),
+        };
+
+        let mut page = Some(format!("{}?per_page=100", self.build_base_url(vec!["releases"])?));
+        while let Some(url) = page {
+            let uri = match url.parse::<HyperUri>() {
+                Ok(uri) => uri,
+                Err(_) => return Err(GitHubError::UnableToMakeURI),
+            };
+
+            let (releases, next) = self.handle_paged_request(uri)?;
+            if let Some(release) = releases.into_iter().find(|x| x["tag_name"].as_str() == Some(tag)) {
+                return Ok(Some(release));
+            }
+
+            page = next;
+        }
+
+        return Ok(None);
+    }
+
+    /// One page of a list, along with the url of the next page from the `Link` header.
+    fn handle_paged_request(&self, uri: HyperUri) -> Result<(Vec<JsonValue>, Option<String>), GitHubError> {
+        trace!("Page to get {:?}", uri);
+
+        let mut request = Request::new(Method::Get, uri);
+        http::set_default_headers(
+            request.headers_mut(),
+            Some("application/vnd.github.v3+json"),
+            Some(self.api_token.clone()),
+        );
+        return match self.requester.make_request_with_headers(request) {
+            Err(err) => Err(GitHubError::CommunicationError(err)),
+            Ok((StatusCode::Ok, headers, body)) => {
+                let next = headers
+                    .get_raw("Link")
+                    .and_then(|x| x.one())
+                    .and_then(|x| str::from_utf8(x).ok())
+                    .and_then(next_page);
+
+                match parse(&body) {
+                    Ok(JsonValue::Array(items)) => Ok((items, next)),
+                    _ => Err(GitHubError::UnableToParseResponse),
+                }
+            }
+            Ok((status, _, _)) => {
+                debug!("Status code was {}", status);
+                Err(GitHubError::UnableToCreateRelease(status))
+            }
+        };
+    }
+
+    /// The commit `tag` points at, peeling annotated tags. `None` when the tag doesn't exist.
+    fn find_tag_commit(&self, tag: &str) -> Result<Option<String>, GitHubError> {
+        let uri = self.build_base_url(vec!["git", "refs", "tags", tag])?;
+        let response = match self.handle_network_request_without_body(uri) {
+            Ok(response) => response,
+            Err(GitHubError::UnableToCreateRelease(StatusCode::NotFound)) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let reference = match exact_reference(response, &format!("refs/tags/{}", tag)) {
+            Some(reference) => reference,
+            None => return Ok(None),
+        };
+
+        let (object_type, sha) = match (reference["object"]["type"].as_str(), reference["object"]["sha"].as_str()) {
+            (Some(object_type), Some(sha)) => (s!(object_type), s!(sha)),
+            _ => return Err(GitHubError::UnableToParseResponse),
+        };
+
+        if object_type != "tag" {
+            return Ok(Some(sha));
+        }
+
+        let tag_object = self.handle_network_request_without_body(self.build_base_url(vec!["git", "tags", &sha])?)?;
+        return match tag_object["object"]["sha"].as_str() {
+            Some(sha) => Ok(Some(s!(sha))),
+            None => Err(GitHubError::UnableToParseResponse),
+        };
+    }
+
+    fn handle_delete_request(&self, uri: HyperUri, name: &str) -> Result<(), GitHubError> {
+        trace!("Deleting {:?}", uri);
+
+        let mut request = Request::new(Method::Delete, uri);
+        http::set_default_headers(
+            request.headers_mut(),
+            Some("application/vnd.github.v3+json"),
+            Some(self.api_token.clone()),
+        );
+        return match self.requester.make_request(request) {
+            Err(err) => Err(GitHubError::CommunicationError(err)),
+            Ok((StatusCode::NoContent, _)) => Ok(()),
+            Ok((status, _)) => {
+                error!("Unable to delete {}, status code was {}", name, status);
+                Err(GitHubError::UnableToDeleteAsset(s!(name)))
+            }
+        };
+    }
+
+    fn validate_files(artifacts: &BTreeMap<String, ReleaseArtifact>) -> Result<(), GitHubError> {
         let missing_files: Vec<String> = artifacts
             .into_iter()
-            .filter(|&(_, value)| !value.exists())
+            .filter(|&(_, value)| !value.path.exists())
             .map(|(key, _)| key.to_string())
             .collect();
 
@@ -166,6 +308,7 @@ impl GitHubImpl {
         &self,
         base_upload_url: String,
         name: String,
+        label: Option<String>,
         file_path: PathBuf,
     ) -> Request {
         let mut uri = Url::parse(&base_upload_url).expect("Url to be valid");
@@ -182,6 +325,9 @@ impl GitHubImpl {
             let mut query = uri.query_pairs_mut();
             query.clear();
             query.append_pair("name", &name);
+            if let Some(label) = label {
+                query.append_pair("label", &label);
+            }
         }
 
         let hyper_uri = uri.as_str().parse::<HyperUri>().unwrap();
@@ -202,10 +348,40 @@ impl GitHubImpl {
     }
 }
 
+/// GitHub answers a ref that doesn't exist with every ref that starts with it, so asking
+/// for `v1.0.0` while only `v1.0.0-rc.1` exists returns an array instead of a ref.
+fn exact_reference(response: JsonValue, ref_name: &str) -> Option<JsonValue> {
+    if !response.is_array() {
+        return Some(response);
+    }
+
+    return response.members().find(|x| x["ref"].as_str() == Some(ref_name)).cloned();
+}
+
+/// The url of the next page in a `Link` header like `<https://...?page=2>; rel="next", <...>; rel="last"`.
+fn next_page(link: &str) -> Option<String> {
+    return link.split(',')
+        .find(|x| x.contains("rel=\"next\""))
+        .and_then(|x| {
+            let start = x.find('<')? + 1;
+            let end = x.find('>')?;
+            Some(s!(&x[start..end]))
+        });
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn will_only_use_exact_tag_reference() {
+        let prefixed = parse(r#"[{"ref": "refs/tags/v1.0.0-rc.1", "object": {"sha": "abc", "type": "commit"}}]"#).unwrap();
+        assert_eq!(exact_reference(prefixed, "refs/tags/v1.0.0"), None);
+
+        let exact = parse(r#"{"ref": "refs/tags/v1.0.0", "object": {"sha": "def", "type": "commit"}}"#).unwrap();
+        assert_eq!(exact_reference(exact, "refs/tags/v1.0.0").unwrap()["object"]["sha"], "def");
+    }
+
     #[test]
     fn will_build_correct_base_url() {
         let github = GitHubImpl {
@@ -219,12 +395,32 @@ mod test {
         let request = github.build_upload_request(
             s!("https://uploads.github.com/repos/ethankhall/release-manager/releases/9989994/assets{?name,label}"), 
             s!("foo"), 
+            None,
             PathBuf::from(format!("{}/LICENSE",out_dir)));
         assert_eq!(
             request.uri().path(),
             "/repos/ethankhall/release-manager/releases/9989994/assets"
         );
         assert_eq!(request.uri().query(), Some("name=foo"));
+
+        let request = github.build_upload_request(
+            s!("https://uploads.github.com/repos/ethankhall/release-manager/releases/9989994/assets{?name,label}"),
+            s!("foo"),
+            Some(s!("Linux build")),
+            PathBuf::from(format!("{}/LICENSE", out_dir)));
+        assert_eq!(request.uri().query(), Some("name=foo&label=Linux+build"));
+    }
+
+    #[test]
+    fn will_find_next_page() {
+        let link = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/releases?per_page=100&page=5>; rel="last""#;
+        assert_eq!(
+            next_page(link),
+            Some(s!("https://api.github.com/repositories/1/releases?per_page=100&page=2"))
+        );
+
+        let last = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=4>; rel="prev""#;
+        assert_eq!(next_page(last), None);
     }
 }
 
@@ -234,22 +430,58 @@ impl GitHub for GitHubImpl {
         id: String,
         version: Version,
         body: String,
-        draft: bool,
+        options: ReleaseOptions,
     ) -> Result<(), GitHubError> {
-        let uri = self.build_base_url(vec!["releases"])?;
-        debug!("URL to post to: {}", uri);
-
         let release_name = format!("v{}", version.to_string());
-        let body = object!{
+        let mut body = object!{
             "tag_name" => release_name.clone(),
-            "target_commitish" => id,
-            "name" => release_name,
+            "target_commitish" => id.clone(),
+            "name" => release_name.clone(),
             "body" => body,
-            "draft" => draft,
-            "prerelease" => false
+            "draft" => options.draft,
+            "prerelease" => options.prerelease
+        };
+
+        if let Some(make_latest) = options.make_latest {
+            body["make_latest"] = make_latest.into();
+        }
+
+        let existing = match self.find_release_by_tag(&release_name)? {
+            None => {
+                let uri = self.build_base_url(vec!["releases"])?;
+                debug!("URL to post to: {}", uri);
+
+                return match self.handle_network_request_with_body(uri, Method::Post, body) {
+                    Ok(_) => Ok(()),
+                    Err(x) => Err(x),
+                };
+            }
+            Some(existing) => existing,
         };
 
-        return match self.handle_network_request_with_body(uri, Method::Post, body) {
+        // A retried job finds the release it already made. The tag decides which commit was
+        // released, or the target of the release while the tag doesn't exist yet.
+        let released_commit = match self.find_tag_commit(&release_name)? {
+            Some(commit) => commit,
+            None => s!(existing["target_commitish"].as_str().unwrap_or("")),
+        };
+
+        if released_commit != id {
+            return Err(GitHubError::ReleaseConflict {
+                tag: release_name,
+                existing: released_commit,
+                requested: id,
+            });
+        }
+
+        let release_id = match existing["id"].as_u64() {
+            Some(release_id) => release_id.to_string(),
+            None => return Err(GitHubError::UnableToParseResponse),
+        };
+
+        info!(target: "user", "Release {} already exists, updating it", release_name);
+        let uri = self.build_base_url(vec!["releases", &release_id])?;
+        return match self.handle_network_request_with_body(uri, Method::Patch, body) {
             Ok(_) => Ok(()),
             Err(x) => Err(x),
         };
@@ -260,6 +492,7 @@ impl GitHub for GitHubImpl {
         head: String,
         branch_name: String,
         files: HashMap<String, String>,
+        message: String,
     ) -> Result<(), GitHubError> {
         let mut tree_entries: Vec<JsonValue> = vec![];
 
@@ -291,7 +524,7 @@ impl GitHub for GitHubImpl {
         trace!("New Tree ID: {:?}", tree_id);
 
         let body = object! {
-            "message" => "Updating the to the next version.\n[skip ci]\n***NO_CI***",
+            "message" => format!("{}\n[skip ci]\n***NO_CI***", message),
             "tree" => tree_id,
             "parents" => vec![head],
             "committer" => object!{
@@ -329,7 +562,8 @@ impl GitHub for GitHubImpl {
     fn add_artifacts_to_release(
         &self,
         release_name: String,
-        artifacts: BTreeMap<String, PathBuf>,
+        artifacts: BTreeMap<String, ReleaseArtifact>,
+        existing_assets: ExistingAssets,
     ) -> Result<(), GitHubError> {
         match GitHubImpl::validate_files(&artifacts) {
             Ok(_) => {}
@@ -338,33 +572,65 @@ impl GitHub for GitHubImpl {
             }
         }
 
-        let response = match self.handle_network_request_without_body(self.build_base_url(vec![
-            "releases",
-            "tags",
-            &release_name,
-        ])?) {
-            Err(err) => return Err(err),
-            Ok(x) => x,
+        let response = match self.find_release_by_tag(&release_name)? {
+            Some(release) => release,
+            None => return Err(GitHubError::UnableToFindRelease),
         };
 
+        let attached: Vec<JsonValue> = response["assets"].members().cloned().collect();
         let upload_url = match response {
             JsonValue::Object(obj) => s!(obj.get("upload_url").unwrap().as_str().unwrap()),
             _ => return Err(GitHubError::UnableToFindRelease),
         };
 
-        let upload_requests: Vec<(String, Request)> = artifacts
-            .into_iter()
-            .map(|(name, path)| {
-                (
-                    name.clone(),
-                    self.build_upload_request(upload_url.clone(), name, path),
-                )
-            })
+        let already_attached: Vec<String> = artifacts
+            .keys()
+            .filter(|name| attached.iter().any(|x| x["name"].as_str() == Some(name.as_str())))
+            .cloned()
             .collect();
 
-        for (name, request) in upload_requests {
+        if existing_assets == ExistingAssets::Fail && !already_attached.is_empty() {
+            return Err(GitHubError::AssetsAlreadyExist(already_attached));
+        }
+
+        // Everything is checked before anything is deleted, and each asset is only deleted
+        // right before its replacement is uploaded.
+        let mut uploads: Vec<(String, ReleaseArtifact, Option<String>)> = Vec::new();
+        for (name, artifact) in artifacts {
+            let replaced = match attached.iter().find(|x| x["name"].as_str() == Some(name.as_str())) {
+                Some(asset) if existing_assets == ExistingAssets::Skip => {
+                    let size = artifact.path.metadata().map(|x| x.len()).ok();
+                    if asset["size"].as_u64() != size {
+                        return Err(GitHubError::AssetSizeDiffers(name));
+                    }
+
+                    info!(target: "user", "Skipping {}, it's already attached", name);
+                    continue;
+                }
+                Some(asset) => match asset["id"].as_u64() {
+                    Some(asset_id) => Some(asset_id.to_string()),
+                    None => return Err(GitHubError::UnableToParseResponse),
+                },
+                None => None,
+            };
+
+            uploads.push((name, artifact, replaced));
+        }
+
+        for (name, artifact, replaced) in uploads {
+            let request = self.build_upload_request(upload_url.clone(), name.clone(), artifact.label, artifact.path);
+
+            if let Some(asset_id) = replaced {
+                debug!("Deleting asset {} ({}) to upload it again", name, asset_id);
+                self.handle_delete_request(self.build_base_url(vec!["releases", "assets", &asset_id])?, &name)?;
+            }
+
             match self.requester.make_request(request) {
-                Ok(_) => {}
+                Ok((StatusCode::Created, _)) => {}
+                Ok((status, _)) => {
+                    error!("Unable to upload {}, status code was {}", name, status);
+                    return Err(GitHubError::UnableToUploadArtifact);
+                }
                 Err(code) => {
                     error!("Error transmitting file {} => {:?}", name, code);
                     return Err(GitHubError::UnableToUploadArtifact);
@@ -374,4 +640,37 @@ impl GitHub for GitHubImpl {
 
         return Ok(());
     }
+
+    fn publish_release(&self, release_name: String, expected_assets: Vec<String>) -> Result<(), GitHubError> {
+        let release = match self.find_release_by_tag(&release_name)? {
+            Some(release) => release,
+            None => return Err(GitHubError::UnableToFindRelease),
+        };
+
+        let attached: Vec<&str> = release["assets"].members().filter_map(|x| x["name"].as_str()).collect();
+        let missing: Vec<String> = expected_assets
+            .into_iter()
+            .filter(|x| !attached.contains(&x.as_str()))
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(GitHubError::MissingAssets(missing));
+        }
+
+        if release["draft"].as_bool() == Some(false) {
+            debug!("Release {} is already published", release_name);
+            return Ok(());
+        }
+
+        let release_id = match release["id"].as_u64() {
+            Some(release_id) => release_id.to_string(),
+            None => return Err(GitHubError::UnableToParseResponse),
+        };
+
+        let uri = self.build_base_url(vec!["releases", &release_id])?;
+        return match self.handle_network_request_with_body(uri, Method::Patch, object!{ "draft" => false }) {
+            Ok(_) => Ok(()),
+            Err(x) => Err(x),
+        };
+    }
 }
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use glob::{glob, Pattern};
+
+use super::api::ReleaseArtifact;
+use super::super::super::config::Config;
+use super::super::super::errors::*;
+
+/// Values for the placeholders in asset names and labels.
+pub(crate) struct Placeholders {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) target: Option<String>,
+}
+
+impl Placeholders {
+    fn render(&self, template: &str, path: &Path) -> Result<String, CommandError> {
+        let target = match self.target {
+            Some(ref target) => target.as_str(),
+            None if template.contains("{target}") => {
+                return Err(CommandError::new(
+                    ErrorCodes::InvalidConfig,
+                    format!("`{}` uses {{target}}, but no --target was given", template),
+                ))
+            }
+            None => "",
+        };
+
+        return Ok(template
+            .replace("{name}", &self.name)
+            .replace("{version}", &self.version)
+            .replace("{target}", target)
+            .replace("{filename}", &file_name(path)));
+    }
+}
+
+/// Artifacts from the command line, each `path`, `name=path` or `name=path#label`. The path
+/// can be a glob, which has to match at least one file.
+pub(crate) fn artifacts_from_args(
+    values: Vec<&str>,
+    project_root: &Path,
+    placeholders: &Placeholders,
+) -> Result<BTreeMap<String, ReleaseArtifact>, CommandError> {
+    let mut artifacts = BTreeMap::new();
+
+    for value in values {
+        let (value, label) = match value.rfind('#') {
+            Some(index) => (&value[..index], Some(&value[index + 1..])),
+            None => (value, None),
+        };
+
+        let (name, path) = match value.find('=') {
+            Some(index) => (Some(&value[..index]), &value[index + 1..]),
+            None => (None, value),
+        };
+
+        add_matching_files(&mut artifacts, project_root, placeholders, path, name, label, true)?;
+    }
+
+    return Ok(artifacts);
+}
+
+/// Artifacts from the `[[github.artifacts]]` entries in the config.
+pub(crate) fn artifacts_from_config(
+    config: &Config,
+    project_root: &Path,
+    placeholders: &Placeholders,
+) -> Result<BTreeMap<String, ReleaseArtifact>, CommandError> {
+    let entries = match config.github.artifacts {
+        Some(ref entries) if !entries.is_empty() => entries,
+        _ => {
+            return Err(CommandError::new(
+                ErrorCodes::InvalidConfig,
+                "No files given and there are no [[github.artifacts]] in the config",
+            ))
+        }
+    };
+
+    let mut artifacts = BTreeMap::new();
+    for entry in entries {
+        add_matching_files(
+            &mut artifacts,
+            project_root,
+            placeholders,
+            &entry.path,
+            entry.name.as_ref().map(|x| x.as_str()),
+            entry.label.as_ref().map(|x| x.as_str()),
+            entry.required.unwrap_or(true),
+        )?;
+    }
+
+    return Ok(artifacts);
+}
+
+fn add_matching_files(
+    artifacts: &mut BTreeMap<String, ReleaseArtifact>,
+    project_root: &Path,
+    placeholders: &Placeholders,
+    path: &str,
+    name: Option<&str>,
+    label: Option<&str>,
+    required: bool,
+) -> Result<(), CommandError> {
+    let files = find_files(project_root, path)?;
+
+    if files.is_empty() {
+        if !required {
+            debug!("Nothing matches {}, skipping it", path);
+            return Ok(());
+        }
+
+        return Err(if is_pattern(path) {
+            CommandError::new(
+                ErrorCodes::ArtifactNotFound,
+                format!("`{}` doesn't match any files", path),
+            )
+        } else {
+            CommandError::new(
+                ErrorCodes::FileDoesNotExist,
+                format!("File(s) `{}` do not exist", path),
+            )
+        });
+    }
+
+    for file in files {
+        let asset_name = match name {
+            Some(name) => placeholders.render(name, &file)?,
+            None => file_name(&file),
+        };
+
+        let asset_label = match label {
+            Some(label) => Some(placeholders.render(label, &file)?),
+            None => None,
+        };
+
+        if let Some(existing) = artifacts.get(&asset_name) {
+            return Err(CommandError::new(
+                ErrorCodes::InvalidConfig,
+                format!(
+                    "{:?} and {:?} would both be uploaded as {}",
+                    existing.path, file, asset_name
+                ),
+            ));
+        }
+
+        debug!("File to upload: {} -> {:?} ({:?})", asset_name, file, asset_label);
+        artifacts.insert(
+            asset_name,
+            ReleaseArtifact {
+                path: file,
+                label: asset_label,
+            },
+        );
+    }
+
+    return Ok(());
+}
+
+fn is_pattern(path: &str) -> bool {
+    return path.contains(|c| c == '*' || c == '?' || c == '[');
+}
+
+/// The files `path` matches, relative to the project root unless it's absolute, in order.
+/// A path without any glob characters matches itself when it exists.
+fn find_files(project_root: &Path, path: &str) -> Result<Vec<PathBuf>, CommandError> {
+    if !is_pattern(path) {
+        let file_path = project_root.join(path);
+        return Ok(if file_path.exists() { vec![file_path] } else { vec![] });
+    }
+
+    let pattern = if Path::new(path).is_absolute() {
+        s!(path)
+    } else {
+        format!("{}/{}", Pattern::escape(project_root.to_str().unwrap()), path)
+    };
+
+    return match glob(&pattern) {
+        Ok(paths) => Ok(paths.filter_map(Result::ok).filter(|x| x.is_file()).collect()),
+        Err(err) => Err(CommandError::new(
+            ErrorCodes::InvalidConfig,
+            format!("`{}` is not a valid glob: {}", path, err),
+        )),
+    };
+}
+
+fn file_name(path: &Path) -> String {
+    return path.file_name().and_then(|x| x.to_str()).map(|x| s!(x)).unwrap_or_default();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_render_asset_names() {
+        let placeholders = Placeholders {
+            name: s!("release-manager"),
+            version: s!("1.2.3"),
+            target: Some(s!("x86_64-unknown-linux-gnu")),
+        };
+        let path = Path::new("target/release/release-manager");
+
+        assert_eq!(
+            placeholders.render("{name}-{version}-{target}.tar.gz", path).ok().unwrap(),
+            "release-manager-1.2.3-x86_64-unknown-linux-gnu.tar.gz"
+        );
+        assert_eq!(placeholders.render("{filename}-{version}", path).ok().unwrap(), "release-manager-1.2.3");
+
+        let placeholders = Placeholders { target: None, ..placeholders };
+        assert!(placeholders.render("{name}-{target}", path).is_err());
+    }
+
+    #[test]
+    fn will_match_absolute_globs() {
+        let fixtures = format!("{}/tests/fixtures", env!("CARGO_MANIFEST_DIR"));
+        let files = find_files(Path::new("/does-not-exist"), &format!("{}/*.md", fixtures)).ok().unwrap();
+
+        assert_eq!(files, vec![PathBuf::from(format!("{}/CHANGELOG.md", fixtures))]);
+    }
+}
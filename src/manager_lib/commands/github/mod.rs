@@ -1,17 +1,23 @@
-use std::collections::BTreeMap;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::Path;
 
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 
-use self::api::{GitHub, GitHubError, GitHubImpl};
+use self::api::{ExistingAssets, GitHub, GitHubError, GitHubImpl, ReleaseOptions};
+use self::artifacts::{artifacts_from_args, artifacts_from_config, Placeholders};
+use super::super::changelog::{promote_unreleased, render_release_notes, PromotedChangelog};
+use super::super::commits::{find_bump_level, BumpLevel};
 use super::super::config::Config;
+use super::super::diff::diff_rendered_files;
 use super::super::errors::*;
 use super::super::git;
-use super::super::version_manager::{build_project, project_from_path, Project};
+use super::super::prerelease::{next_prerelease, prerelease_label};
+use super::super::scheme::VersionScheme;
+use super::super::version_manager::build_project;
 use super::cli_shared;
 
 mod api;
+mod artifacts;
 
 pub fn github_clap<'a, 'b>() -> App<'a, 'b> {
     let github_command = SubCommand::with_name("artifacts")
@@ -19,9 +25,19 @@ pub fn github_clap<'a, 'b>() -> App<'a, 'b> {
         .about("Add artifacts to github release")
         .arg(cli_shared::github_token())
         .arg(Arg::with_name("file")
-            .help("Files to be uploaded. Supports both `path`, and `name=path`. When name is omitted, the filename will be used.")
-            .multiple(true)
-            .required(true));
+            .help("Files to be uploaded. Supports `path`, `name=path` and `name=path#label`, where path can be a glob. When name is omitted, the filename will be used. Without any files, the [[github.artifacts]] in the config are uploaded.")
+            .multiple(true))
+        .arg(Arg::with_name("target")
+            .long("target")
+            .help("Value for {target} in asset names, like x86_64-unknown-linux-gnu")
+            .takes_value(true))
+        .arg(Arg::with_name("overwrite")
+            .long("overwrite")
+            .help("Delete assets that are already attached with the same name and upload them again")
+            .conflicts_with("skip-existing"))
+        .arg(Arg::with_name("skip-existing")
+            .long("skip-existing")
+            .help("Skip assets that are already attached with the same name and size"));
 
     let create_release = SubCommand::with_name("release-and-bump")
         .about("Tag the current branch with the version in the metadata file for the project then bump the patch version.")
@@ -29,6 +45,7 @@ pub fn github_clap<'a, 'b>() -> App<'a, 'b> {
         .arg(Arg::with_name("draft-release")
             .long("draft")
             .help("Release in GitHub will be marked as draft"))
+        .args(&release_options())
         .arg(cli_shared::message())
         .arg(cli_shared::message_file())
         .group(cli_shared::message_group());
@@ -41,13 +58,28 @@ pub fn github_clap<'a, 'b>() -> App<'a, 'b> {
                 .long("draft")
                 .help("Release in GitHub will be marked as draft"),
         )
+        .args(&release_options())
         .arg(cli_shared::message())
         .arg(cli_shared::message_file())
         .group(cli_shared::message_group());
 
+    let publish = SubCommand::with_name("publish")
+        .about("Publish the draft release for the current version.")
+        .arg(cli_shared::github_token())
+        .arg(
+            Arg::with_name("expect-asset")
+                .long("expect-asset")
+                .help("Name of an asset that has to be attached before the release is published")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        );
+
     let bump = SubCommand::with_name("bump")
         .about("Bump the current version on GitHub.")
-        .arg(cli_shared::github_token());
+        .arg(cli_shared::github_token().required_unless(cli_shared::DRY_RUN))
+        .arg(cli_shared::auto_bump())
+        .arg(cli_shared::dry_run());
 
     return App::new("github")
         .about("Upload artifacts to different sources.")
@@ -55,18 +87,40 @@ pub fn github_clap<'a, 'b>() -> App<'a, 'b> {
         .subcommand(github_command)
         .subcommand(create_release)
         .subcommand(release)
+        .subcommand(publish)
         .subcommand(bump);
 }
 
+fn release_options<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    return vec![
+        Arg::with_name("prerelease")
+            .long("prerelease")
+            .help("Mark the release as a pre-release, by default only versions like 2.0.0-rc.1 are")
+            .conflicts_with("no-prerelease"),
+        Arg::with_name("no-prerelease")
+            .long("no-prerelease")
+            .help("Never mark the release as a pre-release"),
+        Arg::with_name("make-latest")
+            .long("make-latest")
+            .help("Whether GitHub shows the release as Latest. Overrides `make-latest` in [github].")
+            .takes_value(true)
+            .possible_values(&["true", "false", "legacy"]),
+    ];
+}
+
 pub fn process_github_command(args: &ArgMatches, config: &Config, project_root: &Path) -> i32 {
     let response = match args.subcommand() {
         ("artifacts", Some(sub_m)) => upload_github_artifacts(sub_m, config, project_root),
         ("release-and-bump", Some(sub_m)) => match create_release(sub_m, config, project_root) {
-            Ok(_) => bump_version(sub_m, config, project_root),
+            Ok(changelog) => bump_version(sub_m, config, project_root, changelog),
+            Err(err) => Err(err),
+        },
+        ("release", Some(sub_m)) => match create_release(sub_m, config, project_root) {
+            Ok(changelog) => commit_changelog(sub_m, config, project_root, changelog),
             Err(err) => Err(err),
         },
-        ("release", Some(sub_m)) => create_release(sub_m, config, project_root),
-        ("bump", Some(sub_m)) => bump_version(sub_m, config, project_root),
+        ("publish", Some(sub_m)) => publish_release(sub_m, config, project_root),
+        ("bump", Some(sub_m)) => bump_version(sub_m, config, project_root, None),
         _ => Err(CommandError::new(
             ErrorCodes::Unknown,
             format!("No command avaliable. {:?}", args),
@@ -95,45 +149,101 @@ fn make_github(args: &ArgMatches, config: &Config) -> Result<GitHubImpl, Command
 }
 
 fn upload_github_artifacts(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
-    let project = get_project(config)?;
-
-    let mut file_map: BTreeMap<String, PathBuf> = BTreeMap::new();
-    let files = args.values_of("file");
-    files.unwrap().for_each(|f| {
-        let (key, value) = if f.contains("=") {
-            let split: Vec<&str> = f.split("=").collect();
-            (split[0], split[1])
-        } else {
-            let pathbuf = Path::new(f);
-            (pathbuf.file_name().and_then(|x| x.to_str()).unwrap(), f)
-        };
-
-        let mut file_path = project_root.to_path_buf();
-        file_path.push(value);
-
-        debug!("File to upload: {} -> {:?}", key, file_path);
-        file_map.insert(s!(key), file_path);
-    });
+    let project = build_project(config, project_root)?;
+
+    let placeholders = Placeholders {
+        name: config.github.repo.clone(),
+        version: project.get_version().to_string(),
+        target: args.value_of("target").map(|x| s!(x)),
+    };
+
+    let file_map = match args.values_of("file") {
+        Some(files) => artifacts_from_args(files.collect(), project_root, &placeholders)?,
+        None => artifacts_from_config(config, project_root, &placeholders)?,
+    };
+
+    if file_map.is_empty() {
+        info!(target: "user", "Nothing to upload");
+        return Ok(());
+    }
+
+    let existing_assets = if args.is_present("overwrite") {
+        ExistingAssets::Overwrite
+    } else if args.is_present("skip-existing") {
+        ExistingAssets::Skip
+    } else {
+        ExistingAssets::Fail
+    };
 
     let github = make_github(args, config)?;
     let release = format!("v{}", project.get_version());
 
-    return match github.add_artifacts_to_release(release, file_map) {
+    return match github.add_artifacts_to_release(release.clone(), file_map, existing_assets) {
         Err(GitHubError::FilesDoesNotExist(files)) => Err(CommandError::new(
             ErrorCodes::FileDoesNotExist,
             format!("File(s) `{}` do not exist", files.join(", ")),
         )),
-        _ => Ok(()),
+        Err(GitHubError::AssetsAlreadyExist(names)) => Err(CommandError::new(
+            ErrorCodes::AssetAlreadyExists,
+            format!(
+                "Release {} already has {}. Use --overwrite to replace them or --skip-existing to leave them.",
+                release,
+                names.join(", ")
+            ),
+        )),
+        Err(GitHubError::AssetSizeDiffers(name)) => Err(CommandError::new(
+            ErrorCodes::AssetAlreadyExists,
+            format!(
+                "Release {} already has a different {}. Use --overwrite to replace it.",
+                release, name
+            ),
+        )),
+        Err(GitHubError::UnableToFindRelease) => Err(CommandError::new(
+            ErrorCodes::ReleaseNotFound,
+            format!("Unable to find a release for {}", release),
+        )),
+        Err(GitHubError::UnableToDeleteAsset(name)) => Err(CommandError::new(
+            ErrorCodes::UnableToDeleteAsset,
+            format!("Unable to delete {} from release {} to replace it", name, release),
+        )),
+        Err(err) => {
+            trace!("Unable to upload artifacts: {:?}", err);
+            Err(CommandError::new(
+                ErrorCodes::GitHubError,
+                format!("Unable to upload artifacts to release {}", release),
+            ))
+        }
+        Ok(_) => Ok(()),
     };
 }
 
-fn create_release(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+/// Creates the release, returning the changelog with its Unreleased section promoted so it
+/// can be committed afterwards.
+fn create_release(
+    args: &ArgMatches,
+    config: &Config,
+    project_root: &Path,
+) -> Result<Option<PromotedChangelog>, CommandError> {
 
-    let project = get_project(config)?;
+    let project = build_project(config, project_root)?;
     let version = project.get_version();
+    VersionScheme::from_config(config)?.validate(&version)?;
+
+    let changelog = promote_unreleased(config, project_root, &version);
 
-    let message_contents =
-        cli_shared::extract_message(args, format!("Tagging version {}.", version.to_string()));
+    let default_message = match changelog {
+        Some(ref changelog) => changelog.notes.clone(),
+        None => match render_release_notes(project_root, &version) {
+            Ok(Some(notes)) => notes,
+            Ok(None) => format!("Tagging version {}.", version.to_string()),
+            Err(err) => {
+                warn!("Unable to build release notes: {}", err.message);
+                format!("Tagging version {}.", version.to_string())
+            }
+        },
+    };
+
+    let message_contents = cli_shared::extract_message(args, default_message);
 
     let github = make_github(args, config)?;
 
@@ -142,12 +252,34 @@ fn create_release(args: &ArgMatches, config: &Config, project_root: &Path) -> Re
         Ok(v) => v,
     };
 
-    return match github.create_release(
-        head,
-        version,
-        message_contents,
-        args.is_present("draft-release"),
-    ) {
+    let make_latest = match args.value_of("make-latest") {
+        Some(value) => Some(s!(value)),
+        None => config.github.make_latest.clone(),
+    };
+
+    if let Some(ref value) = make_latest {
+        if !["true", "false", "legacy"].contains(&value.as_str()) {
+            return Err(CommandError::new(
+                ErrorCodes::InvalidConfig,
+                format!("make-latest `{}` should be one of true, false or legacy", value),
+            ));
+        }
+    }
+
+    let options = ReleaseOptions {
+        draft: args.is_present("draft-release"),
+        prerelease: (version.is_prerelease() || args.is_present("prerelease")) && !args.is_present("no-prerelease"),
+        make_latest: make_latest,
+    };
+
+    return match github.create_release(head, version, message_contents, options) {
+        Err(GitHubError::ReleaseConflict { tag, existing, requested }) => Err(CommandError::new(
+            ErrorCodes::ReleaseConflict,
+            format!(
+                "Release {} already exists for commit {}, but HEAD is {}. Bump the version or delete the old release before releasing again.",
+                tag, existing, requested
+            ),
+        )),
         Err(v) => {
             trace!("Unable to create release! {:?}", v);
             Err(CommandError::new(
@@ -155,26 +287,122 @@ fn create_release(args: &ArgMatches, config: &Config, project_root: &Path) -> Re
                 s!("Unable to create release"),
             ))
         }
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(changelog),
     };
 }
 
-fn get_project(config: &Config) -> Result<Arc<Project>, CommandError> {
-    let project =  match config.clone().github.verion_file {
-        Some(file) => project_from_path(file),
-        None => build_project(None)
+fn publish_release(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let release = format!("v{}", project.get_version());
+
+    let expected_assets: Vec<String> = match args.values_of("expect-asset") {
+        Some(values) => values.map(|x| s!(x)).collect(),
+        None => Vec::new(),
     };
 
-    return match project {
-        Some(project) => Ok(project),
-        None => return Err(CommandError::new(ErrorCodes::NoRepoFound, "Could not find project description!"))
+    let github = make_github(args, config)?;
+    return match github.publish_release(release.clone(), expected_assets) {
+        Ok(_) => {
+            info!(target: "user", "Published release {}", release);
+            Ok(())
+        }
+        Err(GitHubError::UnableToFindRelease) => Err(CommandError::new(
+            ErrorCodes::ReleaseNotFound,
+            format!("Unable to find a release for {}", release),
+        )),
+        Err(GitHubError::MissingAssets(missing)) => Err(CommandError::new(
+            ErrorCodes::MissingReleaseAssets,
+            format!("Release {} is missing {}, not publishing it", release, missing.join(", ")),
+        )),
+        Err(err) => {
+            trace!("Unable to publish release: {:?}", err);
+            Err(CommandError::new(
+                ErrorCodes::GitHubError,
+                format!("Unable to publish release {}", release),
+            ))
+        }
     };
 }
 
-fn bump_version(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+fn commit_changelog(
+    args: &ArgMatches,
+    config: &Config,
+    project_root: &Path,
+    changelog: Option<PromotedChangelog>,
+) -> Result<(), CommandError> {
+    let changelog = match changelog {
+        Some(changelog) => changelog,
+        None => return Ok(()),
+    };
+
+    let mut files = HashMap::new();
+    files.insert(changelog.path, changelog.text);
+
+    return commit_files(
+        args,
+        config,
+        project_root,
+        files,
+        s!("Moving the unreleased changes in the changelog to the release."),
+        "Unable to update the changelog in GitHub",
+    );
+}
+
+fn bump_version(
+    args: &ArgMatches,
+    config: &Config,
+    project_root: &Path,
+    changelog: Option<PromotedChangelog>,
+) -> Result<(), CommandError> {
    
-    let project = get_project(config)?;
+    let project = build_project(config, project_root)?;
+
+    let scheme = VersionScheme::from_config(config)?;
+    let current_version = project.get_version();
+
+    let version = if args.is_present(cli_shared::AUTO_BUMP) {
+        scheme.bump(&current_version, find_bump_level(config, project_root)?)?
+    } else if let Some(label) = prerelease_label(&current_version) {
+        next_prerelease(&current_version, &label, &scheme)?
+    } else {
+        scheme.bump(&current_version, BumpLevel::Patch)?
+    };
+    info!(target: "user", "Next version will be {}", version);
+
+    let mut version_files = project.render_version_files(version);
+    if let Some(changelog) = changelog {
+        version_files.insert(changelog.path, changelog.text);
+    }
+
+    if version_files.is_empty() {
+        info!(target: "user", "No version files to update, skipping the bump commit");
+        return Ok(());
+    }
 
+    if args.is_present(cli_shared::DRY_RUN) {
+        print!("{}", diff_rendered_files(project_root, &version_files));
+        return Ok(());
+    }
+
+    return commit_files(
+        args,
+        config,
+        project_root,
+        version_files,
+        s!("Updating the to the next version."),
+        "Unable to bump version in GitHub",
+    );
+}
+
+/// Commits the files on top of HEAD, on the branch HEAD belongs to.
+fn commit_files(
+    args: &ArgMatches,
+    config: &Config,
+    project_root: &Path,
+    files: HashMap<String, String>,
+    message: String,
+    failure: &str,
+) -> Result<(), CommandError> {
     let head = match git::find_last_commit(project_root.to_path_buf()) {
         Err(err) => return Err(CommandError::new(err, "Unable to get last commit")),
         Ok(v) => v,
@@ -185,20 +413,12 @@ fn bump_version(args: &ArgMatches, config: &Config, project_root: &Path) -> Resu
         Ok(v) => v,
     };
 
-    let mut version = project.get_version();
-
     let github = make_github(args, config)?;
-    version.increment_patch();
-    let version_files = project.render_version_files(version);
-
-    return match github.update_files(head, branch_name, version_files) {
+    return match github.update_files(head, branch_name, files, message) {
         Ok(_) => Ok(()),
         Err(err) => {
-            trace!("Unable to bump version: {:?}", err);
-            Err(CommandError::new(
-                ErrorCodes::UnableToBumpVersion,
-                s!("Unable to bump version in GitHub"),
-            ))
+            trace!("{}: {:?}", failure, err);
+            Err(CommandError::new(ErrorCodes::UnableToBumpVersion, failure))
         }
     };
 }
@@ -1,12 +1,23 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::Path;
 
 use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
 use semver::Identifier;
 use semver::Version as SemverVersion;
 
+use super::super::changelog::{changelog_path, prepend_section, render_release_notes, render_section};
+use super::super::check::{report_json, run_checks, CheckStatus};
+use super::super::commits::{find_bump_level, BumpLevel};
 use super::super::config::Config;
+use super::super::diff::diff_rendered_files;
 use super::super::errors::*;
-use super::super::version_manager::build_project;
+use super::super::file::{read_file_to_string, write_file};
+use super::super::git;
+use super::super::prerelease::{next_prerelease, promote};
+use super::super::scheme::VersionScheme;
+use super::super::snapshot::snapshot_version;
+use super::super::version_manager::{build_project, configured_project, find_candidates, find_project_file,
+                                    search_path, Project};
+use super::cli_shared;
 
 pub fn project_clap<'a, 'b>() -> App<'a, 'b> {
     let create_command = SubCommand::with_name("update-version")
@@ -37,6 +48,20 @@ pub fn project_clap<'a, 'b>() -> App<'a, 'b> {
                 .long("snapshot")
                 .help("Update the version, to a snapshot version"),
         )
+        .arg(cli_shared::auto_bump())
+        .arg(cli_shared::dry_run())
+        .arg(
+            Arg::with_name("bump-pre")
+                .long("bump-pre")
+                .value_name("label")
+                .help("Move to the next pre-release with the label, like alpha, beta or rc")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("promote")
+                .long("promote")
+                .help("Turn the current pre-release into a release"),
+        )
         .group(
             ArgGroup::with_name("version-options")
                 .required(true)
@@ -46,22 +71,74 @@ pub fn project_clap<'a, 'b>() -> App<'a, 'b> {
                     "bump-minor",
                     "bump-patch",
                     "snapshot",
+                    "auto",
+                    "bump-pre",
+                    "promote",
                 ]),
         );
 
-    let show_version = SubCommand::with_name("show-version").about("Show the current version");
+    let show_version = SubCommand::with_name("show-version")
+        .about("Show the current version")
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .help("Print the version with its parts, next versions and project details")
+                .takes_value(true)
+                .possible_values(&["json", "env", "shell"]),
+        );
+
+    let changelog = SubCommand::with_name("changelog")
+        .about("Add a section for the current version to the changelog, from the commits since the last release")
+        .arg(
+            Arg::with_name("stdout")
+                .long("stdout")
+                .help("Print the section instead of adding it to the changelog"),
+        );
+
+    let commit_version = SubCommand::with_name("commit-version")
+        .about("Commit the files that hold the version, using `commit-message` from [version] in the config");
+
+    let tag = SubCommand::with_name("tag")
+        .about("Tag HEAD with the current version")
+        .arg(cli_shared::message())
+        .arg(cli_shared::message_file())
+        .group(cli_shared::message_group());
+
+    let check = SubCommand::with_name("check")
+        .about("Check the project is ready to be released, configured with [check]")
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .help("How to print the report")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text"),
+        );
+
+    let list_projects = SubCommand::with_name("list-projects")
+        .about("Show the project files that were found, and which one is used for the version");
 
     return App::new("local")
         .about("Local project operations.")
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .subcommand(create_command)
-        .subcommand(show_version);
+        .subcommand(show_version)
+        .subcommand(changelog)
+        .subcommand(commit_version)
+        .subcommand(tag)
+        .subcommand(check)
+        .subcommand(list_projects);
 }
 
-pub fn process_project_command(args: &ArgMatches, _config: &Config) -> i32 {
+pub fn process_project_command(args: &ArgMatches, config: &Config, project_root: &Path) -> i32 {
     let response = match args.subcommand() {
-        ("update-version", Some(m)) => update_version(m),
-        ("show-version", Some(m)) => show_version(m),
+        ("update-version", Some(m)) => update_version(m, config, project_root),
+        ("show-version", Some(m)) => show_version(m, config, project_root),
+        ("list-projects", Some(m)) => list_projects(m, config, project_root),
+        ("changelog", Some(m)) => changelog(m, config, project_root),
+        ("commit-version", Some(m)) => commit_version(m, config, project_root),
+        ("tag", Some(m)) => tag_version(m, config, project_root),
+        ("check", Some(m)) => check_release(m, config, project_root),
         _ => Err(CommandError::new(
             ErrorCodes::Unknown,
             format!("No command avaliable. {:?}", args),
@@ -77,56 +154,302 @@ pub fn process_project_command(args: &ArgMatches, _config: &Config) -> i32 {
     };
 }
 
-fn show_version(_args: &ArgMatches) -> Result<(), CommandError> {
-    let project = build_project(None).unwrap();
-    println!("{}", project.get_version());
+fn show_version(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let version = project.get_version();
+
+    let scheme = VersionScheme::from_config(config)?;
+    scheme.validate(&version)?;
+
+    let format = match args.value_of("format") {
+        Some(format) => format,
+        None => {
+            println!("{}", version);
+            return Ok(());
+        }
+    };
+
+    let version_files = version_file_names(&*project, project_root);
+
+    let join_identifiers = |identifiers: &Vec<Identifier>| -> String {
+        identifiers.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(".")
+    };
+
+    let tag = format!("v{}", version);
+    let (pre, build) = (join_identifiers(&version.pre), join_identifiers(&version.build));
+    let next_patch = scheme.bump(&version, BumpLevel::Patch)?.to_string();
+    let next_minor = scheme.bump(&version, BumpLevel::Minor)?.to_string();
+    let next_major = scheme.bump(&version, BumpLevel::Major)?.to_string();
+
+    let fields: Vec<(&str, String)> = vec![
+        ("VERSION", version.to_string()),
+        ("VERSION_MAJOR", version.major.to_string()),
+        ("VERSION_MINOR", version.minor.to_string()),
+        ("VERSION_PATCH", version.patch.to_string()),
+        ("VERSION_PRE", pre.clone()),
+        ("VERSION_BUILD", build.clone()),
+        ("VERSION_TAG", tag.clone()),
+        ("NEXT_PATCH_VERSION", next_patch.clone()),
+        ("NEXT_MINOR_VERSION", next_minor.clone()),
+        ("NEXT_MAJOR_VERSION", next_major.clone()),
+        ("PROJECT_TYPE", s!(project.get_project_type())),
+        ("VERSION_FILE", version_files.first().cloned().unwrap_or_default()),
+    ];
+
+    match format {
+        "json" => {
+            let output = object!{
+                "version" => version.to_string(),
+                "major" => version.major,
+                "minor" => version.minor,
+                "patch" => version.patch,
+                "pre" => pre,
+                "build" => build,
+                "tag" => tag,
+                "next" => object!{
+                    "patch" => next_patch,
+                    "minor" => next_minor,
+                    "major" => next_major
+                },
+                "project" => object!{
+                    "type" => project.get_project_type(),
+                    "files" => version_files.clone()
+                }
+            };
+            println!("{}", output.pretty(2));
+        }
+        "env" => for &(name, ref value) in fields.iter() {
+            println!("{}={}", name, value);
+        },
+        _ => for &(name, ref value) in fields.iter() {
+            println!("export {}='{}'", name, value.replace("'", "'\\''"));
+        },
+    };
+
+    return Ok(());
+}
+
+/// The version files, relative to the project root, as `show-version` reports them.
+fn version_file_names(project: &Project, project_root: &Path) -> Vec<String> {
+    return project
+        .get_version_files()
+        .iter()
+        .map(|x| s!(x.strip_prefix(project_root).unwrap_or(x).to_string_lossy()))
+        .collect();
+}
+
+fn list_projects(_args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    if let Some((reason, _)) = configured_project(config, project_root)? {
+        println!("Version is configured by {} in the config", reason);
+        return Ok(());
+    }
+
+    for dir in search_path(project_root) {
+        let candidates = find_candidates(config, &dir);
+        if candidates.is_empty() {
+            println!("{}: no project files", dir.display());
+            continue;
+        }
+
+        println!("{}:", dir.display());
+        for candidate in candidates {
+            let file_name = candidate.path.file_name().unwrap().to_string_lossy().into_owned();
+            println!("    {} {}", file_name, candidate.reason);
+        }
+    }
+
+    let selected = find_project_file(config, project_root)?;
+    println!("Using {}", selected.path.display());
+
+    return Ok(());
+}
+
+fn changelog(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let version = project.get_version();
+
+    let notes = match render_release_notes(project_root, &version)? {
+        Some(notes) => notes,
+        None => {
+            info!(target: "user", "None of the commits since the last release belong in the changelog");
+            return Ok(());
+        }
+    };
+
+    let section = render_section(&version, &notes);
+    if args.is_present("stdout") {
+        print!("{}", section);
+        return Ok(());
+    }
+
+    let path = changelog_path(config, project_root);
+    let existing = if path.exists() {
+        read_file_to_string(&path)
+    } else {
+        s!("")
+    };
+
+    write_file(prepend_section(&existing, &version, &section)?, &path);
+    info!(target: "user", "Added {} to {}", version, path.display());
+
+    return Ok(());
+}
+
+fn commit_version(_args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let version = project.get_version();
+
+    let files = project.get_version_files();
+    if files.is_empty() {
+        info!(target: "user", "No version files to commit");
+        return Ok(());
+    }
+
+    let message = config
+        .version
+        .as_ref()
+        .and_then(|x| x.commit_message.clone())
+        .unwrap_or(s!("Updating the version to {version}."))
+        .replace("{version}", &version.to_string());
+
+    return match git::commit_files(project_root.to_path_buf(), &files, message) {
+        Ok(commit) => {
+            info!(target: "user", "Committed version {} as {}", version, commit);
+            Ok(())
+        }
+        Err(err) => Err(CommandError::new(err, "Unable to commit the version files")),
+    };
+}
+
+fn tag_version(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let version = project.get_version();
+
+    let tag_name = format!("v{}", version);
+    let message = cli_shared::extract_message(args, format!("Tagging version {}.", version));
+
+    return match git::create_tag(project_root.to_path_buf(), tag_name.clone(), message) {
+        Ok(_) => {
+            info!(target: "user", "Created tag {}", tag_name);
+            Ok(())
+        }
+        Err(err) => Err(CommandError::new(err, format!("Unable to create tag {}", tag_name))),
+    };
+}
+
+fn check_release(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let version = project.get_version();
+
+    let results = run_checks(config, project_root, &version);
+
+    if args.value_of("format") == Some("json") {
+        println!("{}", report_json(&version, &results).pretty(2));
+    } else {
+        for result in results.iter() {
+            println!("{:>7} {}: {}", result.status.name(), result.name, result.message);
+        }
+    }
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|x| x.status == CheckStatus::Failed)
+        .map(|x| x.name)
+        .collect();
+
+    if !failed.is_empty() {
+        return Err(CommandError::new(
+            ErrorCodes::CheckFailed,
+            format!("{} is not ready to release, failed {}", version, failed.join(", ")),
+        ));
+    }
 
     return Ok(());
 }
 
-fn update_version(args: &ArgMatches) -> Result<(), CommandError> {
-    let project = build_project(None).unwrap();
+fn update_version(args: &ArgMatches, config: &Config, project_root: &Path) -> Result<(), CommandError> {
+    let project = build_project(config, project_root)?;
+    let scheme = VersionScheme::from_config(config)?;
+
     let next_version = if let Some(ver) = args.value_of("at-version") {
-        SemverVersion::parse(ver).expect("Version provided is not acceptable semver version")
+        let version = SemverVersion::parse(ver).expect("Version provided is not acceptable semver version");
+        scheme.validate(&version)?;
+        version
+    } else if let Some(label) = args.value_of("bump-pre") {
+        next_prerelease(&project.get_version(), label, &scheme)?
+    } else if args.is_present("promote") {
+        promote(&project.get_version())?
     } else {
-        let mut version = project.get_version();
+        let version = project.get_version();
 
-        let (maj, min, pat, snapshot) = (
+        let (maj, min, pat, snapshot, auto) = (
             args.is_present("bump-major"),
             args.is_present("bump-minor"),
             args.is_present("bump-patch"),
             args.is_present("snapshot"),
+            args.is_present(cli_shared::AUTO_BUMP),
         );
 
-        match (maj, min, pat, snapshot) {
-            (true, _, _, _) => version.increment_major(),
-            (_, true, _, _) => version.increment_minor(),
-            (_, _, true, _) => version.increment_patch(),
-            (_, _, _, true) => {
-                version = SemverVersion {
-                    major: version.major,
-                    minor: version.minor,
-                    patch: version.patch,
-                    pre: vec![Identifier::AlphaNumeric(s!("SNAPSHOT"))],
-                    build: vec![
-                        Identifier::Numeric(
-                            SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
-                        ),
-                    ],
-                };
-            }
+        match (maj, min, pat, snapshot, auto) {
+            (true, _, _, _, _) => scheme.bump(&version, BumpLevel::Major)?,
+            (_, true, _, _, _) => scheme.bump(&version, BumpLevel::Minor)?,
+            (_, _, true, _, _) => scheme.bump(&version, BumpLevel::Patch)?,
+            (_, _, _, _, true) => scheme.bump(&version, find_bump_level(config, project_root)?)?,
+            (_, _, _, true, _) => snapshot_version(config, project_root, &version)?,
             _ => unreachable!(),
-        };
-
-        version
+        }
     };
 
     let next_version_string = next_version.to_string();
     info!(target: "user", "Next version will be {}", next_version_string);
 
+    if args.is_present(cli_shared::DRY_RUN) {
+        print!("{}", diff_rendered_files(project_root, &project.render_version_files(next_version)));
+        return Ok(());
+    }
+
     project.update_version(next_version);
     return Ok(());
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use toml;
+
+    fn cargo_config(fixture: &str) -> Config {
+        let text = format!(
+            "[github]\nowner = \"foo\"\nrepo = \"bar\"\nversion-file = \"tests/fixtures/{}/Cargo.toml\"\n",
+            fixture
+        );
+        return toml::from_str(&text).unwrap();
+    }
+
+    #[test]
+    fn will_commit_cargo_manifest() {
+        let project_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let project = build_project(&cargo_config("cargo-crate"), project_root).ok().unwrap();
+
+        // These are the files commit-version stages.
+        assert_eq!(
+            project.get_version_files(),
+            vec![project_root.join("tests/fixtures/cargo-crate/Cargo.toml")]
+        );
+    }
+
+    #[test]
+    fn will_report_cargo_version_files() {
+        let project_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let project = build_project(&cargo_config("cargo-workspace"), project_root).ok().unwrap();
+
+        assert_eq!(
+            version_file_names(&*project, project_root),
+            vec![
+                s!("tests/fixtures/cargo-workspace/Cargo.toml"),
+                s!("tests/fixtures/cargo-workspace/crates/cli/Cargo.toml"),
+                s!("tests/fixtures/cargo-workspace/crates/plain/Cargo.toml"),
+                s!("tests/fixtures/cargo-workspace/Cargo.lock"),
+            ]
+        );
+    }
+}
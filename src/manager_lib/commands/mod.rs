@@ -14,6 +14,8 @@ pub(crate) mod cli_shared {
     pub static ARTIFACTORY_API_TOKEN: &'static str = "artifactory-api-token";
     pub static MESSAGE: &'static str = "message";
     pub static MESSAGE_FILE: &'static str = "message-file";
+    pub static AUTO_BUMP: &'static str = "auto";
+    pub static DRY_RUN: &'static str = "dry-run";
 
     pub(crate) fn github_token<'a, 'b>() -> Arg<'a, 'b> {
         return Arg::with_name(GITHUB_API_TOKEN)
@@ -52,6 +54,18 @@ pub(crate) mod cli_shared {
             .number_of_values(1);
     }
 
+    pub(crate) fn auto_bump<'a, 'b>() -> Arg<'a, 'b> {
+        return Arg::with_name(AUTO_BUMP)
+            .long("auto")
+            .help("Pick the bump level from the Conventional Commits since the last version tag");
+    }
+
+    pub(crate) fn dry_run<'a, 'b>() -> Arg<'a, 'b> {
+        return Arg::with_name(DRY_RUN)
+            .long("dry-run")
+            .help("Print a diff of the version files instead of changing them");
+    }
+
     pub(crate) fn message_group<'a>() -> ArgGroup<'a> {
         return ArgGroup::with_name("messages").args(&["message", "message-file"]);
     }
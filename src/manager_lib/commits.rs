@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::vec::Vec;
+
+use semver::Version;
+
+use config::Config;
+use errors::{CommandError, ErrorCodes};
+use git::{self, CommitInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum BumpLevel {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl BumpLevel {
+    fn parse(level: &str) -> Option<BumpLevel> {
+        return match level {
+            "none" => Some(BumpLevel::None),
+            "patch" => Some(BumpLevel::Patch),
+            "minor" => Some(BumpLevel::Minor),
+            "major" => Some(BumpLevel::Major),
+            _ => None,
+        };
+    }
+
+    fn name(&self) -> &'static str {
+        return match *self {
+            BumpLevel::None => "none",
+            BumpLevel::Patch => "patch",
+            BumpLevel::Minor => "minor",
+            BumpLevel::Major => "major",
+        };
+    }
+
+    pub(crate) fn apply(&self, version: &mut Version) {
+        match *self {
+            BumpLevel::Major => version.increment_major(),
+            BumpLevel::Minor => version.increment_minor(),
+            BumpLevel::Patch | BumpLevel::None => version.increment_patch(),
+        };
+    }
+}
+
+/// The parts of a Conventional Commits message used for the bump level and changelog.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ConventionalCommit {
+    pub(crate) kind: String,
+    pub(crate) scope: Option<String>,
+    pub(crate) breaking: bool,
+    pub(crate) description: String,
+}
+
+/// Parses `type(scope)!: description`, with an optional `BREAKING CHANGE:` footer.
+/// Messages that don't follow the format return `None`.
+pub(crate) fn parse_commit(message: &str) -> Option<ConventionalCommit> {
+    let header = message.lines().next()?;
+    let separator = header.find(": ")?;
+    let (prefix, description) = (&header[..separator], header[separator + 2..].trim());
+
+    let (prefix, bang) = if prefix.ends_with('!') {
+        (&prefix[..prefix.len() - 1], true)
+    } else {
+        (prefix, false)
+    };
+
+    let (kind, scope) = match prefix.find('(') {
+        Some(index) if prefix.ends_with(')') => (&prefix[..index], Some(s!(&prefix[index + 1..prefix.len() - 1]))),
+        Some(_) => return None,
+        None => (prefix, None),
+    };
+
+    if kind.is_empty() || !kind.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+
+    let breaking_footer = message
+        .lines()
+        .skip(1)
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+
+    return Some(ConventionalCommit {
+        kind: kind.to_lowercase(),
+        scope: scope,
+        breaking: bang || breaking_footer,
+        description: s!(description),
+    });
+}
+
+/// `feat` is a minor change and `fix` or `perf` a patch, unless `[version.commit-types]`
+/// says otherwise. Breaking changes are always major.
+fn commit_level(commit: &ConventionalCommit, commit_types: &HashMap<String, BumpLevel>) -> BumpLevel {
+    if commit.breaking {
+        return BumpLevel::Major;
+    }
+
+    if let Some(level) = commit_types.get(&commit.kind) {
+        return *level;
+    }
+
+    return match commit.kind.as_str() {
+        "feat" => BumpLevel::Minor,
+        "fix" | "perf" => BumpLevel::Patch,
+        _ => BumpLevel::None,
+    };
+}
+
+fn configured_commit_types(config: &Config) -> Result<HashMap<String, BumpLevel>, CommandError> {
+    let mut commit_types = HashMap::new();
+
+    let configured = config.version.as_ref().and_then(|x| x.commit_types.clone());
+    for (kind, level) in configured.unwrap_or_default() {
+        match BumpLevel::parse(&level) {
+            Some(level) => commit_types.insert(kind.to_lowercase(), level),
+            None => {
+                return Err(CommandError::new(
+                    ErrorCodes::InvalidConfig,
+                    format!(
+                        "`{}` for commit type `{}` is not a bump level, use major, minor, patch or none",
+                        level, kind
+                    ),
+                ))
+            }
+        };
+    }
+
+    return Ok(commit_types);
+}
+
+/// Works out the bump level from the commits since the last version tag, and prints the
+/// commits that decided it. When nothing calls for a release the patch version is bumped.
+pub(crate) fn find_bump_level(config: &Config, project_root: &Path) -> Result<BumpLevel, CommandError> {
+    let commit_types = configured_commit_types(config)?;
+
+    let last_tag = match git::find_latest_version_tag(project_root.to_path_buf()) {
+        Ok(tag) => tag,
+        Err(err) => return Err(CommandError::new(err, "Unable to read version tags")),
+    };
+
+    let since = last_tag.as_ref().map(|x| x.commit.clone());
+    let commits = match git::find_commits_since(project_root.to_path_buf(), since) {
+        Ok(commits) => commits,
+        Err(err) => return Err(CommandError::new(err, "Unable to read commits")),
+    };
+
+    let since_name = match last_tag {
+        Some(ref tag) => format!("since {}", tag.name),
+        None => s!("in the history"),
+    };
+
+    let classified: Vec<(&CommitInfo, BumpLevel)> = commits
+        .iter()
+        .map(|commit| {
+            let level = parse_commit(&commit.message)
+                .map(|x| commit_level(&x, &commit_types))
+                .unwrap_or(BumpLevel::None);
+            (commit, level)
+        })
+        .collect();
+
+    let level = classified.iter().map(|x| x.1).max().unwrap_or(BumpLevel::None);
+    if level == BumpLevel::None {
+        info!(target: "user", "No commits {} call for a release, bumping patch", since_name);
+        return Ok(BumpLevel::Patch);
+    }
+
+    info!(target: "user", "Bumping {} because of these commits {}:", level.name(), since_name);
+    for &(commit, _) in classified.iter().filter(|x| x.1 == level) {
+        info!(target: "user", "    {} {}", &commit.sha[..7], commit.summary);
+    }
+
+    return Ok(level);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_parse_conventional_commits() {
+        assert_eq!(
+            parse_commit("feat(api)!: drop v1 routes"),
+            Some(ConventionalCommit {
+                kind: s!("feat"),
+                scope: Some(s!("api")),
+                breaking: true,
+                description: s!("drop v1 routes"),
+            })
+        );
+
+        let fix = parse_commit("fix: handle empty tags\n\nBREAKING CHANGE: tags are required").unwrap();
+        assert_eq!(fix.kind, "fix");
+        assert!(fix.breaking);
+
+        assert_eq!(parse_commit("Merge pull request #12 from foo/bar"), None);
+        assert_eq!(parse_commit("Update README.md"), None);
+    }
+
+    #[test]
+    fn will_use_configured_commit_types() {
+        let mut commit_types = HashMap::new();
+        commit_types.insert(s!("deps"), BumpLevel::Patch);
+
+        let deps = parse_commit("deps: update hyper").unwrap();
+        let docs = parse_commit("docs: fix typo").unwrap();
+        let feat = parse_commit("feat: add --auto").unwrap();
+
+        assert_eq!(commit_level(&deps, &commit_types), BumpLevel::Patch);
+        assert_eq!(commit_level(&docs, &commit_types), BumpLevel::None);
+        assert_eq!(commit_level(&feat, &commit_types), BumpLevel::Minor);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use toml;
@@ -7,7 +8,12 @@ use super::file;
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub github: GitHubConfig,
-    pub artifactory: Option<ArtifactoryConfig>
+    pub artifactory: Option<ArtifactoryConfig>,
+    pub cargo: Option<CargoConfig>,
+    pub helm: Option<HelmConfig>,
+    pub version: Option<VersionConfig>,
+    pub changelog: Option<ChangelogConfig>,
+    pub check: Option<CheckConfig>
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -15,7 +21,23 @@ pub struct GitHubConfig {
     pub owner: String,
     pub repo: String,
     #[serde(rename = "version-file")]
-    pub verion_file: Option<String>
+    pub verion_file: Option<String>,
+    #[serde(rename = "make-latest")]
+    pub make_latest: Option<String>,
+    pub artifacts: Option<Vec<GitHubArtifactConfig>>
+}
+
+/// A `[[github.artifacts]]` entry, uploaded by `github artifacts` when no files are given.
+#[derive(Debug, Deserialize, Clone)]
+pub struct GitHubArtifactConfig {
+    /// Glob, relative to the project root.
+    pub path: String,
+    /// Asset name, with `{name}`, `{version}`, `{target}` and `{filename}` replaced.
+    /// Defaults to the name of the matched file.
+    pub name: Option<String>,
+    pub label: Option<String>,
+    /// Fail when the glob matches nothing, on unless turned off.
+    pub required: Option<bool>
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -27,6 +49,57 @@ pub struct ArtifactoryConfig {
     pub bintray_repo: Option<String>
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct CargoConfig {
+    pub lockstep: Option<Vec<String>>
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HelmConfig {
+    #[serde(rename = "app-version")]
+    pub app_version: Option<bool>
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct VersionConfig {
+    pub files: Option<Vec<VersionFileConfig>>,
+    pub source: Option<String>,
+    #[serde(rename = "file-name")]
+    pub file_name: Option<String>,
+    #[serde(rename = "commit-types")]
+    pub commit_types: Option<HashMap<String, String>>,
+    #[serde(rename = "snapshot-format")]
+    pub snapshot_format: Option<String>,
+    pub scheme: Option<String>,
+    #[serde(rename = "commit-message")]
+    pub commit_message: Option<String>
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct VersionFileConfig {
+    pub path: String,
+    pub regex: String,
+    pub template: Option<String>
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChangelogConfig {
+    pub path: Option<String>
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CheckConfig {
+    #[serde(rename = "clean-tree")]
+    pub clean_tree: Option<bool>,
+    #[serde(rename = "release-branches")]
+    pub release_branches: Option<Vec<String>>,
+    #[serde(rename = "version-increased")]
+    pub version_increased: Option<bool>,
+    #[serde(rename = "tag-available")]
+    pub tag_available: Option<bool>,
+    pub changelog: Option<bool>
+}
+
 pub fn parse_toml(path: &PathBuf) -> Config {
     return toml::from_str(&file::read_file_to_string(path))
         .expect("Config to be well formed TOML");
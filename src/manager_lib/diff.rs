@@ -0,0 +1,200 @@
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::path::Path;
+use std::vec::Vec;
+
+use file::read_file_to_string;
+
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// A unified diff for every rendered file against what is on disk, in path order. Files
+/// that don't change are left out.
+pub(crate) fn diff_rendered_files(project_root: &Path, files: &HashMap<String, String>) -> String {
+    let mut paths: Vec<&String> = files.keys().collect();
+    paths.sort();
+
+    let mut output = String::new();
+    for path in paths {
+        let file_path = project_root.join(path);
+        let current = if file_path.exists() {
+            read_file_to_string(&file_path)
+        } else {
+            s!("")
+        };
+
+        output.push_str(&unified_diff(path, &current, &files[path]));
+    }
+
+    return output;
+}
+
+pub(crate) fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|&(_, edit)| match *edit {
+            Edit::Equal(_, _) => false,
+            _ => true,
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    if changes.is_empty() {
+        return s!("");
+    }
+
+    let mut output = format!("--- a/{}\n+++ b/{}\n", path, path);
+
+    let mut index = 0;
+    while index < changes.len() {
+        let start = changes[index].saturating_sub(CONTEXT_LINES);
+        let mut end = changes[index];
+        while index < changes.len() && changes[index] <= end + 2 * CONTEXT_LINES {
+            end = changes[index];
+            index += 1;
+        }
+        let end = min(end + CONTEXT_LINES + 1, edits.len());
+
+        output.push_str(&render_hunk(&edits[start..end], &old_lines, &new_lines));
+    }
+
+    return output;
+}
+
+fn render_hunk(edits: &[Edit], old_lines: &[&str], new_lines: &[&str]) -> String {
+    let (mut old_start, mut new_start) = (None, None);
+    let (mut old_count, mut new_count) = (0, 0);
+    let mut body = String::new();
+
+    for edit in edits {
+        match *edit {
+            Edit::Equal(old, new) => {
+                old_start = old_start.or(Some(old));
+                new_start = new_start.or(Some(new));
+                old_count += 1;
+                new_count += 1;
+                body.push_str(&format!(" {}\n", old_lines[old]));
+            }
+            Edit::Delete(old) => {
+                old_start = old_start.or(Some(old));
+                old_count += 1;
+                body.push_str(&format!("-{}\n", old_lines[old]));
+            }
+            Edit::Insert(new) => {
+                new_start = new_start.or(Some(new));
+                new_count += 1;
+                body.push_str(&format!("+{}\n", new_lines[new]));
+            }
+        }
+    }
+
+    // An empty side points at the line before the hunk, which is 0 at the top of the file.
+    let old_start = if old_count == 0 { old_start.unwrap_or(0) } else { old_start.unwrap() + 1 };
+    let new_start = if new_count == 0 { new_start.unwrap_or(0) } else { new_start.unwrap() + 1 };
+
+    return format!("@@ -{},{} +{},{} @@\n{}", old_start, old_count, new_start, new_count, body);
+}
+
+/// Myers' diff, returning the shortest list of edits to turn `old` into `new`.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let offset = max(n + m, 1) as usize;
+    let mut v = vec![0isize; 2 * offset + 2];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..(n + m + 1) {
+        trace.push(v.clone());
+
+        let mut k = -d;
+        while k <= d {
+            let index = (k + offset as isize) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[index] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    let (mut x, mut y) = (n, m);
+    let mut edits = Vec::new();
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let index = (k + offset as isize) as usize;
+
+        let previous_k = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = v[(previous_k + offset as isize) as usize];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+
+        if d > 0 {
+            if x == previous_x {
+                y -= 1;
+                edits.push(Edit::Insert(y as usize));
+            } else {
+                x -= 1;
+                edits.push(Edit::Delete(x as usize));
+            }
+        }
+    }
+
+    edits.reverse();
+    return edits;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_render_unified_diff() {
+        let old = "[package]\nname = \"foo\"\nversion = \"1.2.3\"\nauthors = []\n\n[dependencies]\nbar = \"1\"\nbaz = \"2\"\nqux = \"3\"\n";
+        let new = "[package]\nname = \"foo\"\nversion = \"1.3.0\"\nauthors = []\n\n[dependencies]\nbar = \"1\"\nbaz = \"2\"\nqux = \"3\"\n";
+
+        assert_eq!(
+            unified_diff("Cargo.toml", old, new),
+            "--- a/Cargo.toml\n+++ b/Cargo.toml\n@@ -1,6 +1,6 @@\n [package]\n name = \"foo\"\n\
+             -version = \"1.2.3\"\n+version = \"1.3.0\"\n authors = []\n \n [dependencies]\n"
+        );
+    }
+
+    #[test]
+    fn will_handle_new_and_unchanged_files() {
+        assert_eq!(unified_diff("VERSION", "", "1.0.0\n"), "--- a/VERSION\n+++ b/VERSION\n@@ -0,0 +1,1 @@\n+1.0.0\n");
+        assert_eq!(unified_diff("VERSION", "1.0.0\n", "1.0.0\n"), "");
+    }
+}
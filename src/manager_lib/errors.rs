@@ -12,6 +12,22 @@ pub enum ErrorCodes {
     RepoNotValid,
     ArtifactoryCommunicationFailed,
     ArtifactorySectionDoesNotContainBintray,
+    UnableToReadTags,
+    UnableToCreateTag,
+    ProjectNotFound,
+    AmbiguousProject,
+    UnableToReadCommits,
+    InvalidConfig,
+    InvalidVersion,
+    ChangelogEntryExists,
+    UnableToCommit,
+    CheckFailed,
+    ReleaseConflict,
+    ReleaseNotFound,
+    MissingReleaseAssets,
+    AssetAlreadyExists,
+    ArtifactNotFound,
+    UnableToDeleteAsset,
 }
 
 pub struct CommandError {
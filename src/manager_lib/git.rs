@@ -3,7 +3,20 @@ use std::vec::Vec;
 
 use super::errors::*;
 use git2::Repository as GitRepository;
-use git2::{ObjectType, Oid};
+use git2::{BranchType, ObjectType, Oid, StatusOptions, SORT_TIME, SORT_TOPOLOGICAL};
+use semver::Version;
+
+pub(crate) struct VersionTag {
+    pub(crate) name: String,
+    pub(crate) version: Version,
+    pub(crate) commit: String,
+}
+
+pub(crate) struct CommitInfo {
+    pub(crate) sha: String,
+    pub(crate) summary: String,
+    pub(crate) message: String,
+}
 
 fn find_git_repo(root_path: PathBuf) -> Result<GitRepository, ErrorCodes> {
     return match GitRepository::discover(root_path) {
@@ -69,10 +82,291 @@ pub(crate) fn find_branch_for_commit(
     };
 }
 
+/// Every local and remote branch whose tip is `sha`, without the remote name.
+pub(crate) fn find_branches_for_commit(root_path: PathBuf, sha: String) -> Result<Vec<String>, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+    let oid = Oid::from_str(&sha).map_err(|_| ErrorCodes::UnableToFindBranchNameForSha)?;
+
+    let branches = match repo.branches(None) {
+        Ok(branches) => branches,
+        Err(err) => {
+            error!("Unable to list branches: {}", err.message());
+            return Err(ErrorCodes::UnableToFindBranchNameForSha);
+        }
+    };
+
+    let mut names: Vec<String> = Vec::new();
+    for (branch, branch_type) in branches.filter_map(Result::ok) {
+        if branch.get().target() != Some(oid) {
+            continue;
+        }
+
+        if let Ok(Some(name)) = branch.name() {
+            let name = branch_name(name, branch_type);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    return Ok(names);
+}
+
+/// The branch that's checked out, or `None` when HEAD is detached.
+pub(crate) fn find_current_branch(root_path: PathBuf) -> Result<Option<String>, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+
+    return match repo.head() {
+        Ok(ref head) if head.is_branch() => Ok(head.shorthand().map(|x| s!(x))),
+        Ok(_) => Ok(None),
+        Err(err) => {
+            trace!("Error getting HEAD: {:?}", err);
+            Err(ErrorCodes::UnableToFindBranchNameForSha)
+        }
+    };
+}
+
+/// Lists tracked files with changes that haven't been committed. Untracked files are ignored.
+pub(crate) fn find_dirty_files(root_path: PathBuf) -> Result<Vec<String>, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(false).include_ignored(false);
+
+    let statuses = match repo.statuses(Some(&mut options)) {
+        Ok(statuses) => statuses,
+        Err(err) => {
+            error!("Unable to get the status of the working tree: {}", err.message());
+            return Err(ErrorCodes::NoRepoFound);
+        }
+    };
+
+    return Ok(statuses
+        .iter()
+        .filter_map(|x| x.path().map(|path| s!(path)))
+        .collect());
+}
+
+pub(crate) fn tag_exists(root_path: PathBuf, name: &str) -> Result<bool, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+    return Ok(repo.find_reference(&format!("refs/tags/{}", name)).is_ok());
+}
+
+/// Finds every tag that looks like a version (`v1.2.3` or `1.2.3`) and points at HEAD or one
+/// of it's ancestors, ordered from the lowest to highest version.
+pub(crate) fn find_version_tags(root_path: PathBuf) -> Result<Vec<VersionTag>, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+
+    let head = match repo.head().and_then(|x| x.peel(ObjectType::Commit)) {
+        Ok(obj) => obj.id(),
+        Err(err) => {
+            trace!("Error getting last commit: {:?}", err);
+            error!("Unable to get HEAD from repo");
+            return Err(ErrorCodes::UnableToGetHeadSha);
+        }
+    };
+
+    let tag_names = match repo.tag_names(None) {
+        Ok(names) => names,
+        Err(err) => {
+            error!("Unable to list tags: {}", err.message());
+            return Err(ErrorCodes::UnableToReadTags);
+        }
+    };
+
+    let mut tags: Vec<VersionTag> = Vec::new();
+    for name in tag_names.iter().filter_map(|x| x) {
+        let version = match parse_tag_version(name) {
+            Some(version) => version,
+            None => {
+                trace!("Tag {} is not a version, skipping it", name);
+                continue;
+            }
+        };
+
+        let commit = match repo.revparse_single(&format!("refs/tags/{}", name))
+            .and_then(|x| x.peel(ObjectType::Commit))
+        {
+            Ok(obj) => obj.id(),
+            Err(err) => {
+                debug!("Unable to find commit for tag {}: {}", name, err.message());
+                continue;
+            }
+        };
+
+        let reachable = commit == head || repo.graph_descendant_of(head, commit).unwrap_or(false);
+        if !reachable {
+            trace!("Tag {} is not reachable from HEAD", name);
+            continue;
+        }
+
+        tags.push(VersionTag {
+            name: s!(name),
+            version: version,
+            commit: format!("{}", commit),
+        });
+    }
+
+    tags.sort_by(|a, b| a.version.cmp(&b.version));
+    return Ok(tags);
+}
+
+pub(crate) fn find_latest_version_tag(root_path: PathBuf) -> Result<Option<VersionTag>, ErrorCodes> {
+    return find_version_tags(root_path).map(|mut tags| tags.pop());
+}
+
+/// Lists the commits reachable from HEAD but not from `since`, newest first. Without
+/// `since` the whole history is returned.
+pub(crate) fn find_commits_since(root_path: PathBuf, since: Option<String>) -> Result<Vec<CommitInfo>, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+
+    let mut revwalk = match repo.revwalk() {
+        Ok(revwalk) => revwalk,
+        Err(err) => {
+            error!("Unable to walk commits: {}", err.message());
+            return Err(ErrorCodes::UnableToReadCommits);
+        }
+    };
+
+    revwalk.set_sorting(SORT_TOPOLOGICAL | SORT_TIME);
+
+    let pushed = revwalk.push_head().and_then(|_| match since {
+        Some(ref sha) => Oid::from_str(sha).and_then(|oid| revwalk.hide(oid)),
+        None => Ok(()),
+    });
+
+    if let Err(err) = pushed {
+        error!("Unable to walk commits: {}", err.message());
+        return Err(ErrorCodes::UnableToReadCommits);
+    }
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let commit = match oid.and_then(|x| repo.find_commit(x)) {
+            Ok(commit) => commit,
+            Err(err) => {
+                error!("Unable to read commit: {}", err.message());
+                return Err(ErrorCodes::UnableToReadCommits);
+            }
+        };
+
+        commits.push(CommitInfo {
+            sha: format!("{}", commit.id()),
+            summary: s!(commit.summary().unwrap_or("")),
+            message: s!(commit.message().unwrap_or("")),
+        });
+    }
+
+    return Ok(commits);
+}
+
+/// Stages `files` and commits them on top of HEAD, using the signature from the git config.
+/// Returns the id of the new commit.
+pub(crate) fn commit_files(root_path: PathBuf, files: &[PathBuf], message: String) -> Result<String, ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir.to_path_buf(),
+        None => {
+            error!("Unable to commit in a bare repo");
+            return Err(ErrorCodes::UnableToCommit);
+        }
+    };
+
+    let mut index = match repo.index() {
+        Ok(index) => index,
+        Err(err) => {
+            error!("Unable to read the git index: {}", err.message());
+            return Err(ErrorCodes::UnableToCommit);
+        }
+    };
+
+    for file in files {
+        let relative = file.strip_prefix(&workdir).unwrap_or(file.as_path());
+        if let Err(err) = index.add_path(relative) {
+            error!("Unable to stage {:?}: {}", relative, err.message());
+            return Err(ErrorCodes::UnableToCommit);
+        }
+        debug!("Staged {:?}", relative);
+    }
+
+    let committed = index
+        .write()
+        .and_then(|_| index.write_tree())
+        .and_then(|tree_id| repo.find_tree(tree_id))
+        .and_then(|tree| {
+            let parent = repo.head().and_then(|x| x.peel_to_commit())?;
+            let signature = repo.signature()?;
+            repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &[&parent])
+        });
+
+    return match committed {
+        Ok(oid) => {
+            debug!("Created commit {}", oid);
+            Ok(format!("{}", oid))
+        }
+        Err(err) => {
+            error!("Unable to commit: {}", err.message());
+            Err(ErrorCodes::UnableToCommit)
+        }
+    };
+}
+
+/// Creates an annotated tag on HEAD, using the signature from the git config.
+pub(crate) fn create_tag(root_path: PathBuf, name: String, message: String) -> Result<(), ErrorCodes> {
+    let repo = find_git_repo(root_path)?;
+
+    let head = match repo.head().and_then(|x| x.peel(ObjectType::Commit)) {
+        Ok(obj) => obj,
+        Err(err) => {
+            trace!("Error getting last commit: {:?}", err);
+            error!("Unable to get HEAD from repo");
+            return Err(ErrorCodes::UnableToGetHeadSha);
+        }
+    };
+
+    let signature = match repo.signature() {
+        Ok(signature) => signature,
+        Err(err) => {
+            error!("Unable to get the signature to tag with: {}", err.message());
+            return Err(ErrorCodes::UnableToCreateTag);
+        }
+    };
+
+    return match repo.tag(&name, &head, &signature, &message, false) {
+        Ok(oid) => {
+            debug!("Created tag {} as {}", name, oid);
+            Ok(())
+        }
+        Err(err) => {
+            error!("Unable to create tag {}: {}", name, err.message());
+            Err(ErrorCodes::UnableToCreateTag)
+        }
+    };
+}
+
+fn parse_tag_version(tag_name: &str) -> Option<Version> {
+    let version = if tag_name.starts_with('v') {
+        &tag_name[1..]
+    } else {
+        tag_name
+    };
+
+    return Version::parse(version).ok();
+}
+
 fn strip_remote(branch_name: &str) -> String {
     return s!(branch_name.split("/").last().unwrap());
 }
 
+/// Drops only the remote from a remote branch, so `origin/release/1.x` is `release/1.x`.
+fn branch_name(name: &str, branch_type: BranchType) -> String {
+    return match branch_type {
+        BranchType::Remote => s!(name.splitn(2, '/').last().unwrap()),
+        BranchType::Local => s!(name),
+    };
+}
+
 #[test]
 fn will_remove_origin() {
     assert_eq!(strip_remote("origin/master"), s!("master"));
@@ -82,3 +376,16 @@ fn will_remove_origin() {
 fn will_keep_raw_branch_name() {
     assert_eq!(strip_remote("master"), s!("master"));
 }
+
+#[test]
+fn will_parse_tag_versions() {
+    assert_eq!(parse_tag_version("v1.2.3"), Some(Version::parse("1.2.3").unwrap()));
+    assert_eq!(parse_tag_version("1.2.3-rc.1"), Some(Version::parse("1.2.3-rc.1").unwrap()));
+    assert_eq!(parse_tag_version("release-1"), None);
+}
+
+#[test]
+fn will_keep_slashes_in_branch_names() {
+    assert_eq!(branch_name("origin/release/1.x", BranchType::Remote), s!("release/1.x"));
+    assert_eq!(branch_name("release/1.x", BranchType::Local), s!("release/1.x"));
+}
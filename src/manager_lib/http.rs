@@ -16,7 +16,12 @@ use indicatif::{ProgressBar, ProgressStyle};
 use super::errors::ErrorCodes;
 
 pub(crate) trait HttpRequester {
-    fn make_request(&self, request: Request) -> Result<(StatusCode, String), ErrorCodes>;
+    fn make_request(&self, request: Request) -> Result<(StatusCode, String), ErrorCodes> {
+        return self.make_request_with_headers(request).map(|(status, _, body)| (status, body));
+    }
+
+    /// Like `make_request`, along with the response headers.
+    fn make_request_with_headers(&self, request: Request) -> Result<(StatusCode, Headers, String), ErrorCodes>;
 }
 
 pub(crate) fn set_default_headers(
@@ -55,7 +60,7 @@ impl DefaultHttpRequester {
 }
 
 impl HttpRequester for DefaultHttpRequester {
-    fn make_request(&self, request: Request) -> Result<(StatusCode, String), ErrorCodes> {
+    fn make_request_with_headers(&self, request: Request) -> Result<(StatusCode, Headers, String), ErrorCodes> {
         trace!("Request to be sent: {:?}", &request);
 
         let spinner = ProgressBar::new_spinner();
@@ -69,6 +74,7 @@ impl HttpRequester for DefaultHttpRequester {
         let (mut core, client) = self.make_external_parts();
         let work = client.request(request).and_then(|res| {
             let status = Box::new(res.status());
+            let headers = res.headers().clone();
 
             res.body()
                 .fold(Vec::new(), |mut v, chunk| {
@@ -77,12 +83,12 @@ impl HttpRequester for DefaultHttpRequester {
                 })
                 .and_then(|chunks| {
                     let bdy = String::from_utf8(chunks).unwrap();
-                    future::ok::<_, HyperError>((status, s!(bdy)))
+                    future::ok::<_, HyperError>((status, headers, s!(bdy)))
                 })
         });
 
-        let (status, body) = match core.run(work) {
-            Ok((status, body)) => (status, String::from(body)),
+        let (status, headers, body) = match core.run(work) {
+            Ok((status, headers, body)) => (status, headers, String::from(body)),
             Err(err) => {
                 trace!("Request Error: {:?}", err);
                 error!("Unable to make request becasue `{}`", err.description());
@@ -95,6 +101,6 @@ impl HttpRequester for DefaultHttpRequester {
 
         trace!("Body from API: {}", body);
 
-        return Ok((*status.deref(), body));
+        return Ok((*status.deref(), headers, body));
     }
 }
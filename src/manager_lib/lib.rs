@@ -15,6 +15,7 @@ extern crate json;
 extern crate log;
 extern crate mime;
 extern crate mime_guess;
+extern crate regex;
 extern crate semver;
 #[macro_use]
 extern crate serde_derive;
@@ -36,11 +37,18 @@ macro_rules! s {
     };
 }
 
+pub(crate) mod changelog;
+pub(crate) mod check;
 pub mod commands;
+pub(crate) mod commits;
 pub mod config;
+pub(crate) mod diff;
 pub mod errors;
 pub(crate) mod file;
 pub(crate) mod git;
 pub(crate) mod http;
 pub mod logging;
+pub(crate) mod prerelease;
+pub(crate) mod scheme;
+pub(crate) mod snapshot;
 pub(crate) mod version_manager;
@@ -0,0 +1,109 @@
+use semver::{Identifier, Version};
+
+use commits::BumpLevel;
+use errors::{CommandError, ErrorCodes};
+use scheme::VersionScheme;
+
+/// Moves to the next pre-release with `label`. A release starts the pre-releases for the
+/// next minor version (1.2.0 -> 1.3.0-rc.1, or the current month with CalVer), the same
+/// label counts up (rc.1 -> rc.2) and a new label starts again at 1 (beta.2 -> rc.1). The
+/// new version has to sort after the current one, so going from rc back to alpha is an error.
+pub(crate) fn next_prerelease(
+    version: &Version,
+    label: &str,
+    scheme: &VersionScheme,
+) -> Result<Version, CommandError> {
+    if label.is_empty() || !label.chars().all(|c| c.is_alphanumeric() || c == '-') || label.chars().all(|c| c.is_digit(10)) {
+        return Err(CommandError::new(
+            ErrorCodes::InvalidVersion,
+            format!("`{}` is not a valid pre-release label", label),
+        ));
+    }
+
+    let mut next = if version.is_prerelease() {
+        version.clone()
+    } else {
+        scheme.bump(version, BumpLevel::Minor)?
+    };
+    next.build = Vec::new();
+
+    next.pre = match (version.pre.first(), version.pre.last()) {
+        (Some(&Identifier::AlphaNumeric(ref current)), Some(&Identifier::Numeric(number)))
+            if current == label && version.pre.len() == 2 =>
+        {
+            vec![Identifier::AlphaNumeric(s!(label)), Identifier::Numeric(number + 1)]
+        }
+        _ => vec![Identifier::AlphaNumeric(s!(label)), Identifier::Numeric(1)],
+    };
+
+    if next <= *version {
+        return Err(CommandError::new(
+            ErrorCodes::InvalidVersion,
+            format!("{} would not come after {}, pick a later pre-release label", next, version),
+        ));
+    }
+
+    return Ok(next);
+}
+
+/// Turns a pre-release into the release it leads up to, 1.3.0-rc.2 -> 1.3.0.
+pub(crate) fn promote(version: &Version) -> Result<Version, CommandError> {
+    if !version.is_prerelease() {
+        return Err(CommandError::new(
+            ErrorCodes::InvalidVersion,
+            format!("{} is not a pre-release, there is nothing to promote", version),
+        ));
+    }
+
+    return Ok(Version::new(version.major, version.minor, version.patch));
+}
+
+/// The label of a numbered pre-release like `rc.2`, used to carry on the same kind of
+/// pre-release. Other pre-releases, like `SNAPSHOT` or snapshots with build metadata, have none.
+pub(crate) fn prerelease_label(version: &Version) -> Option<String> {
+    if !version.build.is_empty() || version.pre.len() != 2 {
+        return None;
+    }
+
+    return match (&version.pre[0], &version.pre[1]) {
+        (&Identifier::AlphaNumeric(ref label), &Identifier::Numeric(_)) => Some(label.clone()),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn next(version: &str, label: &str) -> Option<String> {
+        return next_prerelease(&Version::parse(version).unwrap(), label, &VersionScheme::SemVer)
+            .ok()
+            .map(|x| x.to_string());
+    }
+
+    #[test]
+    fn will_move_through_prereleases() {
+        assert_eq!(next("1.2.0", "rc"), Some(s!("1.3.0-rc.1")));
+        assert_eq!(next("1.3.0-rc.1", "rc"), Some(s!("1.3.0-rc.2")));
+        assert_eq!(next("1.3.0-beta.4", "rc"), Some(s!("1.3.0-rc.1")));
+        assert_eq!(next("1.3.0-SNAPSHOT+1516", "rc"), Some(s!("1.3.0-rc.1")));
+        assert_eq!(next("1.3.0-rc.2", "alpha"), None);
+    }
+
+    #[test]
+    fn will_promote_prereleases() {
+        let version = Version::parse("1.3.0-rc.2").unwrap();
+        assert_eq!(promote(&version).ok(), Some(Version::parse("1.3.0").unwrap()));
+        assert!(promote(&Version::parse("1.3.0").unwrap()).is_err());
+    }
+
+    #[test]
+    fn will_only_find_label_of_numbered_prereleases() {
+        let label = |version: &str| prerelease_label(&Version::parse(version).unwrap());
+
+        assert_eq!(label("1.3.0-rc.2"), Some(s!("rc")));
+        assert_eq!(label("1.3.0-SNAPSHOT"), None);
+        assert_eq!(label("1.3.0-dev.4+1a2b3c"), None);
+        assert_eq!(label("1.3.0"), None);
+    }
+}
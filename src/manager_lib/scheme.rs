@@ -0,0 +1,97 @@
+use chrono::{Datelike, Utc};
+use semver::Version;
+
+use commits::BumpLevel;
+use config::Config;
+use errors::{CommandError, ErrorCodes};
+
+/// How versions move forward, set with `scheme` in [version].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum VersionScheme {
+    /// major.minor.patch, where the bump level picks the part to increment.
+    SemVer,
+    /// YYYY.MM.MICRO, where a bump moves to the current month or increments MICRO when
+    /// the version is already for this month, or a later one.
+    CalVer,
+}
+
+impl VersionScheme {
+    pub(crate) fn from_config(config: &Config) -> Result<VersionScheme, CommandError> {
+        let scheme = config.version.as_ref().and_then(|x| x.scheme.clone());
+
+        return match scheme.as_ref().map(|x| x.as_str()) {
+            None | Some("semver") => Ok(VersionScheme::SemVer),
+            Some("calver") => Ok(VersionScheme::CalVer),
+            Some(other) => Err(CommandError::new(
+                ErrorCodes::InvalidConfig,
+                format!("Unknown version scheme `{}`, use semver or calver", other),
+            )),
+        };
+    }
+
+    /// Checks the version fits the scheme, so a CalVer project doesn't end up with 1.2.3.
+    pub(crate) fn validate(&self, version: &Version) -> Result<(), CommandError> {
+        if *self == VersionScheme::CalVer && !is_calver(version) {
+            return Err(CommandError::new(
+                ErrorCodes::InvalidVersion,
+                format!("{} is not a YYYY.MM.MICRO calendar version", version),
+            ));
+        }
+
+        return Ok(());
+    }
+
+    pub(crate) fn bump(&self, version: &Version, level: BumpLevel) -> Result<Version, CommandError> {
+        self.validate(version)?;
+
+        let mut next = version.clone();
+        match *self {
+            VersionScheme::SemVer => level.apply(&mut next),
+            VersionScheme::CalVer => {
+                let today = Utc::now();
+                next = next_calver(version, today.year() as u64, today.month() as u64);
+            }
+        };
+
+        return Ok(next);
+    }
+}
+
+fn is_calver(version: &Version) -> bool {
+    return version.major >= 1000 && version.major <= 9999 && version.minor >= 1 && version.minor <= 12;
+}
+
+/// A version dated after today, from a clock that's behind or `--at-version`, keeps its
+/// month so the next version doesn't go backwards.
+fn next_calver(version: &Version, year: u64, month: u64) -> Version {
+    if (version.major, version.minor) >= (year, month) {
+        let micro = if version.is_prerelease() {
+            version.patch
+        } else {
+            version.patch + 1
+        };
+        return Version::new(version.major, version.minor, micro);
+    }
+
+    return Version::new(year, month, 0);
+}
+
+#[test]
+fn will_roll_calendar_versions() {
+    let version = Version::parse("2018.1.3").unwrap();
+
+    assert_eq!(next_calver(&version, 2018, 1), Version::parse("2018.1.4").unwrap());
+    assert_eq!(next_calver(&version, 2018, 2), Version::parse("2018.2.0").unwrap());
+    assert_eq!(
+        next_calver(&Version::parse("2018.1.4-rc.1").unwrap(), 2018, 1),
+        Version::parse("2018.1.4").unwrap()
+    );
+}
+
+#[test]
+fn will_not_roll_calendar_versions_backwards() {
+    let version = Version::parse("2027.1.0").unwrap();
+
+    assert_eq!(next_calver(&version, 2026, 10), Version::parse("2027.1.1").unwrap());
+    assert_eq!(next_calver(&version, 2027, 2), Version::parse("2027.2.0").unwrap());
+}
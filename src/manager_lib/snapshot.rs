@@ -0,0 +1,182 @@
+use std::env;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::Utc;
+use semver::Version;
+
+use config::Config;
+use errors::{CommandError, ErrorCodes};
+use git;
+
+const DEFAULT_SNAPSHOT_FORMAT: &'static str = "-SNAPSHOT+{epoch}";
+
+/// Environment variables CI systems use for the build number, checked in order.
+const BUILD_NUMBER_VARIABLES: &'static [&str] = &[
+    "BUILD_NUMBER",
+    "GITHUB_RUN_NUMBER",
+    "CI_PIPELINE_IID",
+    "TRAVIS_BUILD_NUMBER",
+    "CIRCLE_BUILD_NUM",
+    "BUILDKITE_BUILD_NUMBER",
+];
+
+/// Builds the snapshot of `version` using `snapshot-format` from [version]. The format is
+/// added after `major.minor.patch` and supports `{epoch}`, `{date}`, `{sha}`, `{commits}`,
+/// `{build}` and `{branch}`.
+pub(crate) fn snapshot_version(
+    config: &Config,
+    project_root: &Path,
+    version: &Version,
+) -> Result<Version, CommandError> {
+    let format = config
+        .version
+        .as_ref()
+        .and_then(|x| x.snapshot_format.clone())
+        .unwrap_or(s!(DEFAULT_SNAPSHOT_FORMAT));
+
+    let mut suffix = format.clone();
+    if suffix.contains("{epoch}") {
+        let epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        suffix = suffix.replace("{epoch}", &epoch.to_string());
+    }
+
+    if suffix.contains("{date}") {
+        suffix = suffix.replace("{date}", &Utc::now().format("%Y%m%d").to_string());
+    }
+
+    if suffix.contains("{sha}") || suffix.contains("{branch}") {
+        let head = match git::find_last_commit(project_root.to_path_buf()) {
+            Ok(head) => head,
+            Err(err) => return Err(CommandError::new(err, "Unable to get last commit")),
+        };
+
+        if suffix.contains("{branch}") {
+            suffix = match find_branch(project_root, &head)? {
+                Some(branch) => suffix.replace("{branch}", &sanitize_identifier(&branch)),
+                None => {
+                    warn!("HEAD is not on a branch, leaving {{branch}} out of the snapshot version");
+                    without_placeholder(&suffix, "{branch}")
+                }
+            };
+        }
+
+        suffix = suffix.replace("{sha}", &head[..7]);
+    }
+
+    if suffix.contains("{commits}") {
+        suffix = suffix.replace("{commits}", &count_commits_since_tag(project_root)?.to_string());
+    }
+
+    if suffix.contains("{build}") {
+        suffix = suffix.replace("{build}", &find_build_number()?);
+    }
+
+    let snapshot = format!("{}.{}.{}{}", version.major, version.minor, version.patch, suffix);
+    return match Version::parse(&snapshot) {
+        Ok(snapshot) => Ok(snapshot),
+        Err(err) => Err(CommandError::new(
+            ErrorCodes::InvalidConfig,
+            format!(
+                "snapshot-format `{}` made `{}`, which is not a semver version: {}",
+                format, snapshot, err
+            ),
+        )),
+    };
+}
+
+/// The branch that's checked out, or one at HEAD when it's detached. The full name is used,
+/// so `feature/foo` isn't cut down to `foo`.
+fn find_branch(project_root: &Path, head: &str) -> Result<Option<String>, CommandError> {
+    let current = match git::find_current_branch(project_root.to_path_buf()) {
+        Ok(current) => current,
+        Err(err) => return Err(CommandError::new(err, "Unable to get branch name")),
+    };
+
+    if current.is_some() {
+        return Ok(current);
+    }
+
+    return match git::find_branches_for_commit(project_root.to_path_buf(), s!(head)) {
+        Ok(branches) => Ok(branches.into_iter().next()),
+        Err(err) => Err(CommandError::new(err, "Unable to get branch name")),
+    };
+}
+
+/// The suffix without `placeholder`. Identifiers left empty are dropped, along with the `-`
+/// or `+` in front of the pre-release or build when nothing is left of it.
+fn without_placeholder(suffix: &str, placeholder: &str) -> String {
+    let mut result = String::new();
+
+    for (index, part) in suffix.split('+').enumerate() {
+        let (sigil, identifiers) = if index > 0 {
+            ("+", part)
+        } else if part.starts_with('-') {
+            ("-", &part[1..])
+        } else {
+            ("", part)
+        };
+
+        let kept: Vec<String> = identifiers
+            .split('.')
+            .map(|x| x.replace(placeholder, ""))
+            .filter(|x| !x.is_empty())
+            .collect();
+
+        if !kept.is_empty() {
+            result.push_str(sigil);
+            result.push_str(&kept.join("."));
+        }
+    }
+
+    return result;
+}
+
+fn count_commits_since_tag(project_root: &Path) -> Result<usize, CommandError> {
+    let since = match git::find_latest_version_tag(project_root.to_path_buf()) {
+        Ok(tag) => tag.map(|x| x.commit),
+        Err(err) => return Err(CommandError::new(err, "Unable to read version tags")),
+    };
+
+    return match git::find_commits_since(project_root.to_path_buf(), since) {
+        Ok(commits) => Ok(commits.len()),
+        Err(err) => Err(CommandError::new(err, "Unable to read commits")),
+    };
+}
+
+fn find_build_number() -> Result<String, CommandError> {
+    for name in BUILD_NUMBER_VARIABLES {
+        if let Ok(value) = env::var(name) {
+            trace!("Using {} for the build number", name);
+            return Ok(value);
+        }
+    }
+
+    return Err(CommandError::new(
+        ErrorCodes::InvalidConfig,
+        format!(
+            "snapshot-format uses {{build}}, but none of {} are set",
+            BUILD_NUMBER_VARIABLES.join(", ")
+        ),
+    ));
+}
+
+/// Branch names can have characters semver doesn't allow, like `/` in `feature/foo`.
+fn sanitize_identifier(value: &str) -> String {
+    return value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .collect();
+}
+
+#[test]
+fn will_sanitize_branch_names() {
+    assert_eq!(sanitize_identifier("feature/JIRA-12_foo"), "feature-JIRA-12-foo");
+}
+
+#[test]
+fn will_leave_out_missing_placeholders() {
+    assert_eq!(without_placeholder("-SNAPSHOT.{branch}.{build}", "{branch}"), "-SNAPSHOT.{build}");
+    assert_eq!(without_placeholder("-{branch}+{sha}", "{branch}"), "+{sha}");
+    assert_eq!(without_placeholder("-dev.{commits}+{branch}", "{branch}"), "-dev.{commits}");
+}
@@ -0,0 +1,928 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use glob::glob;
+use regex::{Captures, Match, Regex};
+use semver::{Version, VersionReq};
+use toml_edit::{value, Document, Item, Table, Value};
+
+use config::CargoConfig;
+use errors::{CommandError, ErrorCodes};
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+const CARGO_TOML_NAME: &'static str = "Cargo.toml";
+const CARGO_LOCK_NAME: &'static str = "Cargo.lock";
+const DEPENDENCY_TABLES: &'static [&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// A `key = value` line, where the key can be dotted.
+const KEY_VALUE: &'static str =
+    r#"^\s*((?:[A-Za-z0-9_-]+|"[^"]*"|'[^']*')(?:\s*\.\s*(?:[A-Za-z0-9_-]+|"[^"]*"|'[^']*'))*)\s*=\s*"#;
+/// A basic or literal string at the start of a value.
+const STRING_VALUE: &'static str = r#"^(?:"([^"\\]*)"|'([^']*)')"#;
+
+pub(crate) struct CargoProject {
+    project_path: String,
+    version_file: String,
+    lockstep: Option<Vec<String>>,
+}
+
+/// A single Cargo.toml, either the root manifest or one of the workspace members.
+struct CargoManifest {
+    project_path: String,
+    file: PathBuf,
+    text: String,
+    /// The parsed manifest. When it has dotted keys they're commented out first, as
+    /// toml_edit can't parse them, and `text` is edited line by line instead.
+    doc: Document,
+    has_dotted_keys: bool,
+}
+
+impl CargoProject {
+    /// Loads the workspace up front, so a manifest that can't be parsed or edited is an
+    /// error before anything is changed.
+    pub(crate) fn new(project_path: String, path: &Path, config: Option<&CargoConfig>) -> Result<Self, CommandError> {
+        debug!("Project path: {:?}", path);
+        let project = CargoProject {
+            project_path: project_path,
+            version_file: s!(path.to_str().unwrap()),
+            lockstep: config.and_then(|x| x.lockstep.clone()),
+        };
+
+        let manifests = project.load_manifests()?;
+        if let Some(version) = find_version(&manifests, &project.bumped_packages(&manifests)) {
+            if let Ok(version) = Version::parse(&version) {
+                project.render_manifests(&version)?;
+            }
+        }
+
+        return Ok(project);
+    }
+
+    fn get_cargo_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using Cargo.toml located at {:?}", path_buf);
+        return path_buf;
+    }
+
+    /// Loads the root manifest followed by every `[workspace].members` manifest.
+    fn load_manifests(&self) -> Result<Vec<CargoManifest>, CommandError> {
+        let cargo_file = self.get_cargo_file();
+        let root = CargoManifest::load(self.project_path.clone(), cargo_file.clone())?;
+
+        let root_dir = cargo_file.parent().unwrap().to_path_buf();
+        let project_dir = Path::new(&self.project_path).parent().unwrap_or(Path::new("")).to_path_buf();
+
+        let members = root.get_string_array(&["workspace", "members"]);
+        let excluded: Vec<PathBuf> = root.get_string_array(&["workspace", "exclude"])
+            .into_iter()
+            .map(|x| root_dir.join(x))
+            .collect();
+
+        let mut manifests = vec![root];
+        for member in members {
+            let pattern = root_dir.join(&member);
+            let member_dirs: Vec<PathBuf> = glob(pattern.to_str().unwrap())
+                .unwrap()
+                .filter_map(Result::ok)
+                .filter(|x| x.join(CARGO_TOML_NAME).exists())
+                .filter(|x| !excluded.contains(x))
+                .collect();
+
+            if member_dirs.is_empty() {
+                warn!("Workspace member `{}` did not match any crates", member);
+            }
+
+            for member_dir in member_dirs {
+                let relative = member_dir.strip_prefix(&root_dir).unwrap().join(CARGO_TOML_NAME);
+                let project_path = project_dir.join(&relative);
+                manifests.push(CargoManifest::load(
+                    s!(project_path.to_str().unwrap()),
+                    member_dir.join(CARGO_TOML_NAME),
+                )?);
+            }
+        }
+
+        return Ok(manifests);
+    }
+
+    /// The manifests, which were checked when the project was created.
+    fn manifests(&self) -> Vec<CargoManifest> {
+        return match self.load_manifests() {
+            Ok(manifests) => manifests,
+            Err(err) => panic!("{}", err.message),
+        };
+    }
+
+    /// Names of the packages that are versioned together. Without a `lockstep` list in
+    /// the `[cargo]` config that is every package in the workspace.
+    fn bumped_packages(&self, manifests: &Vec<CargoManifest>) -> Vec<String> {
+        return manifests
+            .iter()
+            .filter_map(|x| x.package_name())
+            .filter(|name| match self.lockstep {
+                Some(ref lockstep) => lockstep.contains(name),
+                None => true,
+            })
+            .collect();
+    }
+
+    fn render_manifests(&self, version: &Version) -> Result<Vec<(String, PathBuf, String)>, CommandError> {
+        let mut manifests = self.load_manifests()?;
+        let bumped = self.bumped_packages(&manifests);
+        let version = version.to_string();
+
+        debug!("Packages being updated to {}: {:?}", version, bumped);
+
+        let mut rendered = Vec::new();
+        for (index, manifest) in manifests.iter_mut().enumerate() {
+            let is_bumped = manifest.is_bumped(&bumped);
+            let changed = manifest.update(index == 0, is_bumped, &bumped, &version)?;
+            if changed {
+                rendered.push((manifest.project_path.clone(), manifest.file.clone(), manifest.to_string()));
+            }
+        }
+
+        if let Some(lock) = self.render_lock_file(&bumped, &version) {
+            rendered.push(lock);
+        }
+
+        return Ok(rendered);
+    }
+
+    fn rendered_manifests(&self, version: &Version) -> Vec<(String, PathBuf, String)> {
+        return match self.render_manifests(version) {
+            Ok(rendered) => rendered,
+            Err(err) => panic!("{}", err.message),
+        };
+    }
+
+    /// Keeps the `[[package]]` entries for the bumped packages in Cargo.lock in sync, so
+    /// the lock file doesn't change on the next build. Packages in the workspace are the
+    /// ones without a `source`.
+    fn render_lock_file(&self, bumped: &Vec<String>, version: &str) -> Option<(String, PathBuf, String)> {
+        let lock_file = self.get_cargo_file().with_file_name(CARGO_LOCK_NAME);
+        if !lock_file.exists() {
+            trace!("No Cargo.lock found at {:?}", lock_file);
+            return None;
+        }
+
+        let text = read_file_to_string(&lock_file);
+        let mut doc = text.parse::<Document>().expect("invalid Cargo.lock");
+        let mut changed = false;
+
+        if let Some(packages) = doc.as_table_mut().entry("package").as_array_of_tables_mut() {
+            for index in 0..packages.len() {
+                let package = match packages.get_mut(index) {
+                    Some(package) => package,
+                    None => continue,
+                };
+
+                let name = package.get("name").and_then(|x| x.as_str()).map(|x| s!(x));
+                let is_local = !package.contains_key("source");
+
+                if let (true, Some(name)) = (is_local, name) {
+                    if bumped.contains(&name) {
+                        *package.entry("version") = value(version);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        if !changed {
+            return None;
+        }
+
+        let lock_path = Path::new(&self.project_path).with_file_name(CARGO_LOCK_NAME);
+        return Some((s!(lock_path.to_str().unwrap()), lock_file, doc.to_string()));
+    }
+}
+
+impl Project for CargoProject {
+    fn get_project_type(&self) -> &'static str {
+        return "cargo";
+    }
+
+    /// The manifests that hold the version, which are the bumped packages with their own
+    /// `version` and the root `[workspace.package]`, followed by Cargo.lock when there is one.
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        let manifests = self.manifests();
+        let bumped = self.bumped_packages(&manifests);
+
+        let mut files: Vec<PathBuf> = manifests
+            .iter()
+            .enumerate()
+            .filter(|&(index, manifest)| {
+                (manifest.is_bumped(&bumped) && manifest.package_version().is_some())
+                    || (index == 0 && manifest.get_str(&["workspace", "package", "version"]).is_some())
+            })
+            .map(|(_, manifest)| manifest.file.clone())
+            .collect();
+
+        let lock_file = self.get_cargo_file().with_file_name(CARGO_LOCK_NAME);
+        if lock_file.exists() {
+            files.push(lock_file);
+        }
+
+        return files;
+    }
+
+    fn get_version(&self) -> Version {
+        let manifests = self.manifests();
+        let bumped = self.bumped_packages(&manifests);
+
+        let version = match find_version(&manifests, &bumped) {
+            Some(value) => value,
+            None => panic!("Unable to get version for cargo.toml located at {:?}", manifests[0].file),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return Version::parse(&version).unwrap();
+    }
+
+    fn update_version(&self, version: Version) {
+        for (_, file, contents) in self.rendered_manifests(&version) {
+            debug!("Updating {:?}", file);
+            write_file(contents, &file);
+        }
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for (project_path, _, contents) in self.rendered_manifests(&version) {
+            map.insert(project_path, contents);
+        }
+
+        return map;
+    }
+}
+
+/// The version of the root package, then `[workspace.package]`, then the first bumped member.
+fn find_version(manifests: &Vec<CargoManifest>, bumped: &Vec<String>) -> Option<String> {
+    let root = &manifests[0];
+
+    return root.package_version()
+        .or_else(|| root.get_str(&["workspace", "package", "version"]))
+        .or_else(|| {
+            manifests
+                .iter()
+                .filter(|x| x.package_name().map(|name| bumped.contains(&name)) == Some(true))
+                .filter_map(|x| x.package_version())
+                .next()
+        });
+}
+
+/// A manifest is a project when it has it's own version, or is the root of a workspace.
+/// Members that inherit their version are skipped so the workspace root is found instead.
+pub(crate) fn detect(path: &Path) -> Option<String> {
+    let text = read_file_to_string(path);
+    let doc = match parse_manifest(&text) {
+        Ok((doc, _)) => doc,
+        Err(_) => return None,
+    };
+
+    if lookup(doc.as_table(), &["package", "version"]).and_then(|x| x.as_str()).is_some() {
+        return Some(s!("has a [package] version"));
+    }
+
+    if lookup(doc.as_table(), &["workspace"]).is_some() {
+        return Some(s!("declares a [workspace]"));
+    }
+
+    return None;
+}
+
+/// Parses a manifest, commenting out dotted keys like `edition.workspace = true` first when
+/// toml_edit can't parse it. The flag is set when that was needed.
+fn parse_manifest(text: &str) -> Result<(Document, bool), String> {
+    if let Ok(doc) = text.parse::<Document>() {
+        return Ok((doc, false));
+    }
+
+    return match without_dotted_keys(text).parse::<Document>() {
+        Ok(doc) => Ok((doc, true)),
+        Err(err) => Err(err.to_string()),
+    };
+}
+
+impl CargoManifest {
+    fn load(project_path: String, file: PathBuf) -> Result<Self, CommandError> {
+        let text = read_file_to_string(&file);
+        let (doc, has_dotted_keys) = match parse_manifest(&text) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                return Err(CommandError::new(
+                    ErrorCodes::UnableToBumpVersion,
+                    format!("Unable to parse {:?}: {}", file, err),
+                ))
+            }
+        };
+
+        return Ok(CargoManifest {
+            project_path: project_path,
+            file: file,
+            text: text,
+            doc: doc,
+            has_dotted_keys: has_dotted_keys,
+        });
+    }
+
+    fn get(&self, path: &[&str]) -> Option<&Item> {
+        return lookup(self.doc.as_table(), path);
+    }
+
+    fn get_str(&self, path: &[&str]) -> Option<String> {
+        return self.get(path).and_then(|x| x.as_str()).map(|x| s!(x));
+    }
+
+    fn get_string_array(&self, path: &[&str]) -> Vec<String> {
+        return match self.get(path).and_then(|x| x.as_array()) {
+            Some(array) => array.iter().filter_map(|x| x.as_str()).map(|x| s!(x)).collect(),
+            None => Vec::new(),
+        };
+    }
+
+    fn package_name(&self) -> Option<String> {
+        return self.get_str(&["package", "name"]);
+    }
+
+    fn is_bumped(&self, bumped: &Vec<String>) -> bool {
+        return match self.package_name() {
+            Some(ref name) => bumped.contains(name),
+            None => false,
+        };
+    }
+
+    /// The version of the package, when it's not inherited from the workspace.
+    fn package_version(&self) -> Option<String> {
+        return self.get_str(&["package", "version"]);
+    }
+
+    fn update(&mut self, is_root: bool, is_bumped: bool, bumped: &Vec<String>, version: &str) -> Result<bool, CommandError> {
+        if !self.has_dotted_keys {
+            return update_document(&mut self.doc, is_root, is_bumped, bumped, version);
+        }
+
+        let text = update_lines(&self.text, is_root, is_bumped, bumped, version)?;
+
+        // Anything the lines missed would still be changed in the parsed manifest.
+        let mut doc = without_dotted_keys(&text).parse::<Document>().unwrap();
+        if update_document(&mut doc, is_root, is_bumped, bumped, version)? {
+            return Err(CommandError::new(
+                ErrorCodes::UnableToBumpVersion,
+                format!("Unable to update every version in {:?}, it will need to be updated by hand", self.file),
+            ));
+        }
+
+        let changed = text != self.text;
+        self.text = text;
+        self.doc = doc;
+
+        return Ok(changed);
+    }
+
+    fn to_string(&self) -> String {
+        if self.has_dotted_keys {
+            return self.text.clone();
+        }
+
+        return self.doc.to_string();
+    }
+}
+
+/// Updates the package version and the requirements on bumped packages, returning whether
+/// anything changed.
+fn update_document(
+    doc: &mut Document,
+    is_root: bool,
+    is_bumped: bool,
+    bumped: &Vec<String>,
+    version: &str,
+) -> Result<bool, CommandError> {
+    let mut changed = false;
+    let root = doc.as_table_mut();
+
+    if is_bumped {
+        if let Some(package) = get_table_mut(root, "package") {
+            changed |= update_version_key(package, version);
+        }
+    }
+
+    if is_root {
+        if let Some(workspace) = get_table_mut(root, "workspace") {
+            if let Some(package) = get_table_mut(workspace, "package") {
+                changed |= update_version_key(package, version);
+            }
+
+            if let Some(dependencies) = get_table_mut(workspace, "dependencies") {
+                changed |= update_dependencies(dependencies, bumped, version)?;
+            }
+        }
+    }
+
+    for name in DEPENDENCY_TABLES {
+        if let Some(dependencies) = get_table_mut(root, name) {
+            changed |= update_dependencies(dependencies, bumped, version)?;
+        }
+    }
+
+    if let Some(targets) = get_table_mut(root, "target") {
+        let target_names: Vec<String> = targets.iter().map(|(key, _)| s!(key)).collect();
+        for target_name in target_names {
+            if let Some(target) = get_table_mut(targets, &target_name) {
+                for name in DEPENDENCY_TABLES {
+                    if let Some(dependencies) = get_table_mut(target, name) {
+                        changed |= update_dependencies(dependencies, bumped, version)?;
+                    }
+                }
+            }
+        }
+    }
+
+    return Ok(changed);
+}
+
+/// Sets `version` in the table when it's there as a string and different.
+fn update_version_key(table: &mut Table, version: &str) -> bool {
+    match table.get("version").and_then(|x| x.as_str()) {
+        Some(current) if current != version => {}
+        _ => return false,
+    }
+
+    *table.entry("version") = value(version);
+    return true;
+}
+
+fn lookup<'a>(table: &'a Table, path: &[&str]) -> Option<&'a Item> {
+    let (first, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return None,
+    };
+
+    let item = table.get(first)?;
+    if rest.is_empty() {
+        return Some(item);
+    }
+
+    return lookup(item.as_table()?, rest);
+}
+
+fn get_table_mut<'a>(table: &'a mut Table, key: &str) -> Option<&'a mut Table> {
+    if !table.contains_table(key) {
+        return None;
+    }
+
+    return table.entry(key).as_table_mut();
+}
+
+/// Rewrites the `version` requirement of any dependency on a package that's being bumped,
+/// keeping the requirement operator (`=`, `^`, `~`...) that was there before.
+fn update_dependencies(dependencies: &mut Table, bumped: &Vec<String>, version: &str) -> Result<bool, CommandError> {
+    let mut changed = false;
+    let keys: Vec<String> = dependencies.iter().map(|(key, _)| s!(key)).collect();
+
+    for key in keys {
+        let item = dependencies.entry(&key);
+
+        if item.is_inline_table() {
+            let inline = item.as_inline_table_mut().unwrap();
+            let name = inline.get("package").and_then(|x| x.as_str()).unwrap_or(&key[..]).to_string();
+            let requirement = inline.get("version").and_then(|x| x.as_str()).map(|x| s!(x));
+
+            if let (true, Some(requirement)) = (bumped.contains(&name), requirement) {
+                let updated = update_requirement(&requirement, version)?;
+                if updated != requirement {
+                    *inline.get_mut("version").unwrap() = Value::from(updated.as_str());
+                    inline.fmt();
+                    changed = true;
+                }
+            }
+        } else if item.is_table() {
+            let table = item.as_table_mut().unwrap();
+            let name = table.get("package").and_then(|x| x.as_str()).unwrap_or(&key[..]).to_string();
+            let requirement = table.get("version").and_then(|x| x.as_str()).map(|x| s!(x));
+
+            if let (true, Some(requirement)) = (bumped.contains(&name), requirement) {
+                let updated = update_requirement(&requirement, version)?;
+                if updated != requirement {
+                    *table.entry("version") = value(updated);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    return Ok(changed);
+}
+
+/// Points a requirement at `version`, keeping its operator. Only the comparators of a
+/// compound requirement like `>=1.0, <2.0` that no longer match are rewritten, and an
+/// upper bound that doesn't match is an error as there's no way to know the new bound.
+fn update_requirement(requirement: &str, version: &str) -> Result<String, CommandError> {
+    if !requirement.contains(',') {
+        return Ok(replace_comparator(requirement, version));
+    }
+
+    let parsed = Version::parse(version).unwrap();
+    let mut comparators = Vec::new();
+
+    for comparator in requirement.split(',') {
+        let matches = match VersionReq::parse(comparator.trim()) {
+            Ok(req) => req.matches(&parsed),
+            Err(_) => false,
+        };
+
+        if matches {
+            comparators.push(s!(comparator));
+        } else if comparator.trim_left().starts_with('<') {
+            return Err(CommandError::new(
+                ErrorCodes::UnableToBumpVersion,
+                format!("{} is outside of `{}`, the requirement needs to be updated by hand", version, requirement),
+            ));
+        } else {
+            comparators.push(replace_comparator(comparator, version));
+        }
+    }
+
+    return Ok(comparators.join(","));
+}
+
+fn replace_comparator(comparator: &str, version: &str) -> String {
+    let operator: String = comparator
+        .chars()
+        .take_while(|c| !c.is_digit(10))
+        .collect();
+
+    return format!("{}{}", operator, version);
+}
+
+fn is_dependency_table(path: &[String], is_root: bool) -> bool {
+    return match path.len() {
+        1 => DEPENDENCY_TABLES.contains(&path[0].as_str()),
+        2 => is_root && path[0] == "workspace" && path[1] == "dependencies",
+        3 => path[0] == "target" && DEPENDENCY_TABLES.contains(&path[2].as_str()),
+        _ => false,
+    };
+}
+
+/// The same changes as `update_document`, made to each line of a manifest with dotted
+/// keys. Covers `version` under `[package]`, requirements in inline tables, in
+/// `[dependencies.foo]` tables and in dotted keys like `foo.version = "1.0"`.
+fn update_lines(text: &str, is_root: bool, is_bumped: bool, bumped: &Vec<String>, version: &str) -> Result<String, CommandError> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let keys = key_value_lines(&lines);
+    let string_value = Regex::new(STRING_VALUE).unwrap();
+    let inline_version = Regex::new(r#"[{,]\s*version\s*=\s*(?:"([^"\\]*)"|'([^']*)')"#).unwrap();
+    let inline_package = Regex::new(r#"[{,]\s*package\s*=\s*(?:"([^"\\]*)"|'([^']*)')"#).unwrap();
+
+    let mut strings: HashMap<Vec<String>, String> = HashMap::new();
+    for (line, key) in lines.iter().zip(keys.iter()) {
+        if let Some((ref path, offset)) = *key {
+            if let Some(captures) = string_value.captures(&line[offset..]) {
+                strings.insert(path.clone(), s!(string_capture(&captures).as_str()));
+            }
+        }
+    }
+
+    let package_name = |dependency: &[String]| -> String {
+        let mut package_key = dependency.to_vec();
+        package_key.push(s!("package"));
+        return strings.get(&package_key).cloned().unwrap_or(dependency.last().unwrap().clone());
+    };
+
+    let mut output: Vec<String> = Vec::new();
+    for (line, key) in lines.iter().zip(keys.iter()) {
+        let (path, offset) = match *key {
+            Some((ref path, offset)) => (path, offset),
+            None => {
+                output.push(s!(*line));
+                continue;
+            }
+        };
+
+        let value = &line[offset..];
+        let is_version = path.last().map(|x| x == "version") == Some(true);
+        let table = &path[..path.len() - 1];
+
+        let replacement = if is_version && is_bumped && table == ["package"] {
+            Some(s!(version))
+        } else if is_version && is_root && table == ["workspace", "package"] {
+            Some(s!(version))
+        } else if is_version && table.len() > 1 && is_dependency_table(&table[..table.len() - 1], is_root) {
+            match strings.get(path) {
+                Some(requirement) if bumped.contains(&package_name(table)) => Some(update_requirement(requirement, version)?),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        if let Some(replacement) = replacement {
+            let found = string_value.captures(value).map(|x| string_capture(&x));
+            output.push(replace_string(line, offset, found, &replacement));
+            continue;
+        }
+
+        if !value.starts_with('{') || !is_dependency_table(table, is_root) {
+            output.push(s!(*line));
+            continue;
+        }
+
+        let name = match inline_package.captures(value) {
+            Some(captures) => s!(string_capture(&captures).as_str()),
+            None => path.last().unwrap().clone(),
+        };
+
+        match inline_version.captures(value) {
+            Some(ref captures) if bumped.contains(&name) => {
+                let updated = update_requirement(string_capture(captures).as_str(), version)?;
+                output.push(replace_string(line, offset, Some(string_capture(captures)), &updated));
+            }
+            _ => output.push(s!(*line)),
+        }
+    }
+
+    return Ok(output.join("\n"));
+}
+
+fn string_capture<'t>(captures: &Captures<'t>) -> Match<'t> {
+    return captures.get(1).or_else(|| captures.get(2)).unwrap();
+}
+
+/// Swaps the string `found` in the value, which starts at `offset` in the line, for `replacement`.
+fn replace_string(line: &str, offset: usize, found: Option<Match>, replacement: &str) -> String {
+    let found = match found {
+        Some(found) => found,
+        None => return s!(line),
+    };
+
+    return format!(
+        "{}{}{}",
+        &line[..offset + found.start()],
+        replacement,
+        &line[offset + found.end()..]
+    );
+}
+
+/// The full key, including the table it's in, and where the value starts for each
+/// `key = value` line. Headers, comments and lines in multi-line strings are `None`.
+fn key_value_lines(lines: &[&str]) -> Vec<Option<(Vec<String>, usize)>> {
+    let key_value = Regex::new(KEY_VALUE).unwrap();
+    let mut table: Vec<String> = Vec::new();
+    let mut in_string = false;
+
+    return lines
+        .iter()
+        .map(|line| {
+            if in_string {
+                in_string = !toggles_multiline_string(line);
+                return None;
+            }
+            in_string = toggles_multiline_string(line);
+
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                let header = trimmed.trim_left_matches('[');
+                table = split_key(split_outside_quotes(header, ']')[0]);
+                return None;
+            }
+
+            let captures = key_value.captures(line)?;
+            let mut path = table.clone();
+            path.extend(split_key(captures.get(1).unwrap().as_str()));
+
+            return Some((path, captures.get(0).unwrap().end()));
+        })
+        .collect();
+}
+
+/// toml_edit can't parse dotted keys, so they're commented out to read the rest of the
+/// manifest, along with the rest of any value they start that runs over several lines.
+fn without_dotted_keys(text: &str) -> String {
+    let key_value = Regex::new(KEY_VALUE).unwrap();
+    let mut commenting = false;
+    let mut depth = 0;
+    let mut in_string = false;
+
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            if !commenting && !in_string {
+                commenting = match key_value.captures(line) {
+                    Some(captures) => split_key(captures.get(1).unwrap().as_str()).len() > 1,
+                    None => false,
+                };
+            }
+
+            if toggles_multiline_string(line) {
+                in_string = !in_string;
+            }
+
+            if !commenting {
+                return s!(line);
+            }
+
+            depth += bracket_depth(line);
+            if depth <= 0 && !in_string {
+                commenting = false;
+                depth = 0;
+            }
+
+            return format!("#{}", line);
+        })
+        .collect();
+
+    return lines.join("\n");
+}
+
+fn toggles_multiline_string(line: &str) -> bool {
+    return line.matches("\"\"\"").count() % 2 == 1 || line.matches("'''").count() % 2 == 1;
+}
+
+/// How many more brackets and braces the line opens than it closes, outside of strings
+/// and comments.
+fn bracket_depth(line: &str) -> isize {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '#') => break,
+            (None, '[') | (None, '{') => depth += 1,
+            (None, ']') | (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+
+    return depth;
+}
+
+fn split_outside_quotes(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, c) if c == separator => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&text[start..]);
+    return parts;
+}
+
+/// `target.'cfg(unix)'.dependencies` as `["target", "cfg(unix)", "dependencies"]`.
+fn split_key(key: &str) -> Vec<String> {
+    return split_outside_quotes(key, '.')
+        .into_iter()
+        .map(|x| s!(x.trim().trim_matches('"').trim_matches('\'')))
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture(path: &str) -> PathBuf {
+        return PathBuf::from(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), path));
+    }
+
+    #[test]
+    fn will_keep_requirement_operator() {
+        assert_eq!(update_requirement("=1.0.0", "1.1.0").ok().unwrap(), "=1.1.0");
+        assert_eq!(update_requirement("^ 1.0", "1.1.0").ok().unwrap(), "^ 1.1.0");
+        assert_eq!(update_requirement("1.0.0", "1.1.0").ok().unwrap(), "1.1.0");
+    }
+
+    #[test]
+    fn will_only_update_broken_comparators() {
+        assert_eq!(update_requirement(">=1.0, <2.0", "1.1.0").ok().unwrap(), ">=1.0, <2.0");
+        assert_eq!(update_requirement(">=1.2, <2.0", "1.1.0").ok().unwrap(), ">=1.1.0, <2.0");
+        assert!(update_requirement(">=1.0, <2.0", "2.0.0").is_err());
+    }
+
+    #[test]
+    fn will_update_workspace_dependencies() {
+        let text = "[dependencies]\nfoo = { path = \"../foo\", version = \"=1.0.0\" }\nserde = \"1.0\"\n\n[dependencies.bar]\npath = \"../bar\"\nversion = \"1.0.0\"\n";
+        let mut doc = text.parse::<Document>().unwrap();
+        let bumped = vec![s!("foo"), s!("bar")];
+
+        assert!(update_dependencies(
+            get_table_mut(doc.as_table_mut(), "dependencies").unwrap(),
+            &bumped,
+            "1.1.0"
+        ).ok().unwrap());
+        assert_eq!(
+            doc.to_string(),
+            "[dependencies]\nfoo = { path = \"../foo\", version = \"=1.1.0\" }\nserde = \"1.0\"\n\n[dependencies.bar]\npath = \"../bar\"\nversion = \"1.1.0\"\n"
+        );
+    }
+
+    #[test]
+    fn will_load_workspace_with_dotted_keys() {
+        let project = CargoProject::new(s!("Cargo.toml"), &fixture("cargo-workspace/Cargo.toml"), None)
+            .ok()
+            .unwrap();
+        let manifests = project.manifests();
+
+        assert_eq!(
+            project.bumped_packages(&manifests),
+            vec![s!("fixture-cli"), s!("fixture-core"), s!("fixture-plain")]
+        );
+        assert!(manifests[2].has_dotted_keys);
+        assert_eq!(manifests[2].package_version(), None);
+        assert_eq!(project.get_version(), Version::parse("1.2.0").unwrap());
+    }
+
+    #[test]
+    fn will_update_workspace_with_dotted_keys() {
+        let project = CargoProject::new(s!("Cargo.toml"), &fixture("cargo-workspace/Cargo.toml"), None)
+            .ok()
+            .unwrap();
+        let files = project.render_version_files(Version::parse("1.3.0").unwrap());
+
+        assert_eq!(
+            files["Cargo.toml"],
+            "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"1.3.0\"\nedition = \"2018\"\n\n\
+             [workspace.dependencies]\nfixture-core = { path = \"crates/core\", version = \"1.3.0\" }\n"
+        );
+        assert_eq!(
+            files["crates/cli/Cargo.toml"],
+            "[package]\nname = \"fixture-cli\"\nversion = \"1.3.0\"\nedition.workspace = true\n\n\
+             [dependencies]\nfixture-core = { path = \"../core\", version = \"=1.3.0\" }\nserde = \"1.0\"\n\n\
+             [dev-dependencies]\ncore.path = \"../core\"\ncore.package = \"fixture-core\"\ncore.version = \"1.3.0\"\n\
+             core.features = [\n    \"test\",\n]\n"
+        );
+        assert_eq!(
+            files["crates/plain/Cargo.toml"],
+            "[package]\nname = \"fixture-plain\"\nversion = \"1.3.0\"\n\n\
+             [dependencies.core]\npackage = \"fixture-core\"\npath = \"../core\"\nversion = \">=1.0, <2.0\"\n"
+        );
+        assert!(!files.contains_key("crates/core/Cargo.toml"));
+    }
+
+    #[test]
+    fn will_list_manifests_holding_the_version() {
+        let project = CargoProject::new(s!("Cargo.toml"), &fixture("cargo-crate/Cargo.toml"), None)
+            .ok()
+            .unwrap();
+        assert_eq!(project.get_version_files(), vec![fixture("cargo-crate/Cargo.toml")]);
+
+        let project = CargoProject::new(s!("Cargo.toml"), &fixture("cargo-workspace/Cargo.toml"), None)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            project.get_version_files(),
+            vec![
+                fixture("cargo-workspace/Cargo.toml"),
+                fixture("cargo-workspace/crates/cli/Cargo.toml"),
+                fixture("cargo-workspace/crates/plain/Cargo.toml"),
+                fixture("cargo-workspace/Cargo.lock"),
+            ]
+        );
+    }
+
+    #[test]
+    fn will_only_update_bumped_packages_in_lock_file() {
+        let config = CargoConfig {
+            lockstep: Some(vec![s!("fixture-cli")]),
+        };
+        let project = CargoProject::new(s!("Cargo.toml"), &fixture("cargo-workspace/Cargo.toml"), Some(&config))
+            .ok()
+            .unwrap();
+
+        let before = read_file_to_string(&fixture("cargo-workspace/Cargo.lock"));
+        let after = project.render_version_files(Version::parse("1.3.0").unwrap())["Cargo.lock"].clone();
+
+        let changed: Vec<(&str, &str)> = before
+            .split('\n')
+            .zip(after.split('\n'))
+            .filter(|&(old, new)| old != new)
+            .collect();
+
+        assert_eq!(before.len(), after.len());
+        assert_eq!(changed, vec![("version = \"1.2.0\"", "version = \"1.3.0\"")]);
+        assert_eq!(
+            after,
+            before.replace(
+                "name = \"fixture-cli\"\nversion = \"1.2.0\"",
+                "name = \"fixture-cli\"\nversion = \"1.3.0\""
+            )
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use regex::{Captures, Match, Regex};
+use semver::Version;
+
+use config::VersionFileConfig;
+use errors::{CommandError, ErrorCodes};
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+/// A project where the version lives in files described by `[[version.files]]` in the
+/// config. The first entry is where the version is read from, every entry is updated.
+pub(crate) struct CustomProject {
+    project_root: PathBuf,
+    files: Vec<VersionFileConfig>,
+}
+
+impl CustomProject {
+    /// Checks every `regex` up front, as one without a group to replace would leave the
+    /// file as it was while the bump looks like it worked.
+    pub(crate) fn new(project_root: &Path, files: Vec<VersionFileConfig>) -> Result<Self, CommandError> {
+        debug!("Project root: {:?}, version files: {:?}", project_root, files);
+
+        for file in files.iter() {
+            let regex = match Regex::new(&file.regex) {
+                Ok(regex) => regex,
+                Err(err) => {
+                    return Err(CommandError::new(
+                        ErrorCodes::InvalidConfig,
+                        format!("Pattern `{}` for {} is not valid: {}", file.regex, file.path, err),
+                    ))
+                }
+            };
+
+            if regex.captures_len() <= 1 {
+                return Err(CommandError::new(
+                    ErrorCodes::InvalidConfig,
+                    format!(
+                        "Pattern `{}` for {} needs a group around the version, like `version = \"(?P<version>[^\"]+)\"`",
+                        file.regex, file.path
+                    ),
+                ));
+            }
+        }
+
+        return Ok(CustomProject {
+            project_root: project_root.to_path_buf(),
+            files: files,
+        });
+    }
+
+    fn get_file_path(&self, file: &VersionFileConfig) -> PathBuf {
+        return self.project_root.join(&file.path);
+    }
+
+    fn render_file(&self, file: &VersionFileConfig, version: &Version) -> String {
+        let file_path = self.get_file_path(file);
+        let text = read_file_to_string(&file_path);
+        let regex = build_regex(file);
+
+        if !regex.is_match(&text) {
+            panic!("Pattern `{}` does not match anything in {:?}", file.regex, file_path);
+        }
+
+        let template = file.template.clone().unwrap_or(s!("{version}"));
+        let replacement = render_template(&template, version);
+
+        return replace_captures(&regex, &text, &replacement);
+    }
+}
+
+impl Project for CustomProject {
+    fn get_project_type(&self) -> &'static str {
+        return "custom";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return self.files.iter().map(|x| self.get_file_path(x)).collect();
+    }
+
+    fn get_version(&self) -> Version {
+        let primary = match self.files.first() {
+            Some(file) => file,
+            None => panic!("No version files configured"),
+        };
+
+        let file_path = self.get_file_path(primary);
+        let text = read_file_to_string(&file_path);
+
+        let version = match build_regex(primary).captures(&text).and_then(|x| version_capture(&x)) {
+            Some(value) => s!(value.as_str()),
+            None => panic!("Unable to find version in {:?} using `{}`", file_path, primary.regex),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return Version::parse(&version).unwrap();
+    }
+
+    fn update_version(&self, version: Version) {
+        for file in self.files.iter() {
+            let text = self.render_file(file, &version);
+            write_file(text, &self.get_file_path(file));
+        }
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for file in self.files.iter() {
+            map.insert(file.path.clone(), self.render_file(file, &version));
+        }
+
+        return map;
+    }
+}
+
+fn build_regex(file: &VersionFileConfig) -> Regex {
+    return match Regex::new(&file.regex) {
+        Ok(regex) => regex,
+        Err(err) => panic!("Pattern `{}` for {} is not valid: {}", file.regex, file.path, err),
+    };
+}
+
+/// The version is taken from the group named `version`, or the first group when there
+/// isn't one with that name.
+fn version_capture<'t>(captures: &Captures<'t>) -> Option<Match<'t>> {
+    return captures.name("version").or_else(|| captures.get(1));
+}
+
+fn render_template(template: &str, version: &Version) -> String {
+    return template
+        .replace("{version}", &version.to_string())
+        .replace("{major}", &version.major.to_string())
+        .replace("{minor}", &version.minor.to_string())
+        .replace("{patch}", &version.patch.to_string());
+}
+
+/// Swaps the version group of every match, keeping the rest of each match as it was.
+fn replace_captures(regex: &Regex, text: &str, replacement: &str) -> String {
+    let mut result = String::new();
+    let mut last_end = 0;
+
+    for captures in regex.captures_iter(text) {
+        if let Some(group) = version_capture(&captures) {
+            result.push_str(&text[last_end..group.start()]);
+            result.push_str(replacement);
+            last_end = group.end();
+        }
+    }
+
+    result.push_str(&text[last_end..]);
+    return result;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_replace_every_match() {
+        let regex = Regex::new(r#"release-manager/(?P<version>[0-9.]+)/"#).unwrap();
+        let text = "curl https://example.com/release-manager/1.2.3/linux\ncurl https://example.com/release-manager/1.2.3/mac\n";
+
+        assert_eq!(
+            replace_captures(&regex, text, "1.3.0"),
+            "curl https://example.com/release-manager/1.3.0/linux\ncurl https://example.com/release-manager/1.3.0/mac\n"
+        );
+    }
+
+    #[test]
+    fn will_reject_pattern_without_group() {
+        let file = VersionFileConfig {
+            path: s!("README.md"),
+            regex: s!("release-manager/[0-9.]+/"),
+            template: None,
+        };
+
+        assert!(CustomProject::new(Path::new("."), vec![file]).is_err());
+    }
+
+    #[test]
+    fn will_render_template() {
+        let version = Version::parse("1.2.3-rc.1").unwrap();
+        assert_eq!(render_template("v{major}.{minor}", &version), "v1.2");
+        assert_eq!(render_template("{version}", &version), "1.2.3-rc.1");
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use semver::Version;
+
+use git;
+use super::Project;
+
+/// A project without a version file. The version is the highest version tag reachable
+/// from HEAD, and updating the version creates a new annotated tag on HEAD.
+pub(crate) struct GitTagProject {
+    project_root: PathBuf,
+}
+
+impl GitTagProject {
+    pub(crate) fn new(project_root: &Path) -> Self {
+        debug!("Using git tags in {:?} for the version", project_root);
+        return GitTagProject {
+            project_root: project_root.to_path_buf(),
+        };
+    }
+}
+
+impl Project for GitTagProject {
+    fn get_project_type(&self) -> &'static str {
+        return "git-tag";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return Vec::new();
+    }
+
+    fn get_version(&self) -> Version {
+        let version = match git::find_latest_version_tag(self.project_root.clone()) {
+            Ok(Some(tag)) => {
+                debug!("Latest version tag is {} at {}", tag.name, tag.commit);
+                tag.version
+            }
+            Ok(None) => {
+                warn!("No version tags found, starting from 0.0.0");
+                Version::new(0, 0, 0)
+            }
+            Err(err) => panic!("Unable to read tags from git: {:?}", err),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return version;
+    }
+
+    fn update_version(&self, version: Version) {
+        let tag_name = format!("v{}", version);
+        let message = format!("Tagging version {}.", version);
+
+        if let Err(err) = git::create_tag(self.project_root.clone(), tag_name, message) {
+            panic!("Unable to tag version {}: {:?}", version, err);
+        }
+    }
+
+    fn render_version_files(&self, _version: Version) -> HashMap<String, String> {
+        return HashMap::new();
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use semver::Version;
+
+use config::HelmConfig;
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+pub(crate) struct HelmProject {
+    project_path: String,
+    version_file: String,
+    track_app_version: bool,
+}
+
+impl HelmProject {
+    pub(crate) fn new(project_path: String, path: &Path, config: Option<&HelmConfig>) -> Self {
+        debug!("Project path: {:?}", path);
+        return HelmProject {
+            project_path: project_path,
+            version_file: s!(path.to_str().unwrap()),
+            track_app_version: config.and_then(|x| x.app_version).unwrap_or(false),
+        };
+    }
+
+    fn get_chart_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using Chart.yaml located at {:?}", path_buf);
+        return path_buf;
+    }
+
+    fn render_chart(&self, version: &Version) -> String {
+        let chart_path = self.get_chart_file();
+        let mut text = read_file_to_string(&chart_path);
+        let version = version.to_string();
+
+        text = match replace_top_level_value(&text, "version", &version) {
+            Some(updated) => updated,
+            None => panic!("Unable to get version for Chart.yaml located at {:?}", chart_path),
+        };
+
+        if self.track_app_version {
+            text = match replace_top_level_value(&text, "appVersion", &version) {
+                Some(updated) => updated,
+                None => {
+                    warn!("Chart.yaml located at {:?} does not have an appVersion to update", chart_path);
+                    text
+                }
+            };
+        }
+
+        return text;
+    }
+}
+
+impl Project for HelmProject {
+    fn get_project_type(&self) -> &'static str {
+        return "helm";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return vec![self.get_chart_file()];
+    }
+
+    fn get_version(&self) -> Version {
+        let chart_path = self.get_chart_file();
+        let text = read_file_to_string(&chart_path);
+
+        let version = match find_top_level_value(&text, "version") {
+            Some((start, end)) => s!(&text[start..end]),
+            None => panic!("Unable to get version for Chart.yaml located at {:?}", chart_path),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return Version::parse(&version).unwrap();
+    }
+
+    fn update_version(&self, version: Version) {
+        let text = self.render_chart(&version);
+        write_file(text, &self.get_chart_file());
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert(self.project_path.clone(), self.render_chart(&version));
+
+        return map;
+    }
+}
+
+fn replace_top_level_value(text: &str, key: &str, new_value: &str) -> Option<String> {
+    let (start, end) = find_top_level_value(text, key)?;
+    return Some(format!("{}{}{}", &text[..start], new_value, &text[end..]));
+}
+
+/// Finds the byte range of a scalar value for a key that isn't nested under anything.
+/// The range excludes any quotes and trailing comment, so both survive an update.
+fn find_top_level_value(text: &str, key: &str) -> Option<(usize, usize)> {
+    let mut line_start = 0;
+
+    for line in text.split('\n') {
+        let offset = line_start;
+        line_start += line.len() + 1;
+
+        let separator = match line.find(':') {
+            Some(index) => index,
+            None => continue,
+        };
+
+        if &line[..separator] != key {
+            continue;
+        }
+
+        let value = &line[separator + 1..];
+        let value_offset = offset + separator + 1 + (value.len() - value.trim_left().len());
+        let value = value.trim_left();
+
+        if value.starts_with('"') || value.starts_with('\'') {
+            let quote = &value[..1];
+            let end = value[1..].find(quote)?;
+            return Some((value_offset + 1, value_offset + 1 + end));
+        }
+
+        let without_comment = match value.find(" #") {
+            Some(index) => &value[..index],
+            None => value,
+        };
+
+        return Some((value_offset, value_offset + without_comment.trim_right().len()));
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_keep_quotes_and_comments() {
+        let text = "apiVersion: v2\nname: foo\n# the chart version\nversion: 1.2.3 # bumped by CI\nappVersion: \"1.2.3\"\ndependencies:\n  - name: bar\n    version: 0.1.0\n";
+
+        let updated = replace_top_level_value(text, "version", "1.3.0").unwrap();
+        let updated = replace_top_level_value(&updated, "appVersion", "1.3.0").unwrap();
+
+        assert_eq!(
+            updated,
+            "apiVersion: v2\nname: foo\n# the chart version\nversion: 1.3.0 # bumped by CI\nappVersion: \"1.3.0\"\ndependencies:\n  - name: bar\n    version: 0.1.0\n"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use semver::Version;
+
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+const POM_XML_NAME: &'static str = "pom.xml";
+
+pub(crate) struct MavenProject {
+    project_path: String,
+    version_file: String,
+}
+
+struct PomUpdate {
+    project_path: String,
+    file: PathBuf,
+    contents: String,
+}
+
+struct XmlElement {
+    path: Vec<String>,
+    start: usize,
+    end: usize,
+}
+
+struct Pom {
+    text: String,
+    elements: Vec<XmlElement>,
+}
+
+impl MavenProject {
+    pub(crate) fn new(project_path: String, path: &Path) -> Self {
+        debug!("Project path: {:?}", path);
+        return MavenProject {
+            project_path: project_path, version_file: s!(path.to_str().unwrap()),
+        };
+    }
+
+    fn get_pom_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using pom.xml located at {:?}", path_buf);
+        return path_buf;
+    }
+
+    /// Builds the new contents of the root pom, and every module pom whose parent is
+    /// the root (or a module that inherits its version from the root).
+    fn collect_updates(&self, version: &Version) -> Vec<PomUpdate> {
+        let pom_file = self.get_pom_file();
+        let pom = Pom::parse(read_file_to_string(&pom_file));
+
+        let old_version = match pom.get(&["project", "version"]) {
+            Some(v) => s!(v),
+            None => panic!("Unable to get version for pom.xml located at {:?}", pom_file),
+        };
+        let new_version = version.to_string();
+
+        let mut updates = vec![PomUpdate {
+            project_path: self.project_path.clone(),
+            file: pom_file.clone(),
+            contents: pom.replace(&["project", "version"], &new_version).unwrap(),
+        }];
+
+        let project_dir = Path::new(&self.project_path).parent().unwrap_or(Path::new("")).to_path_buf();
+        let pom_dir = pom_file.parent().unwrap().to_path_buf();
+        collect_module_updates(&pom, &pom_dir, &project_dir, &old_version, &new_version, &mut updates);
+
+        return updates;
+    }
+}
+
+impl Project for MavenProject {
+    fn get_project_type(&self) -> &'static str {
+        return "maven";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return self.collect_updates(&self.get_version())
+            .into_iter()
+            .map(|update| update.file)
+            .collect();
+    }
+
+    fn get_version(&self) -> Version {
+        let pom_path = self.get_pom_file();
+        let pom = Pom::parse(read_file_to_string(&pom_path));
+
+        let version = match pom.get(&["project", "version"]) {
+            Some(value) => s!(value),
+            None => panic!("Unable to get version for pom.xml located at {:?}", pom_path),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return Version::parse(&version).unwrap();
+    }
+
+    fn update_version(&self, version: Version) {
+        for update in self.collect_updates(&version) {
+            debug!("Updating {:?}", update.file);
+            write_file(update.contents, &update.file);
+        }
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for update in self.collect_updates(&version) {
+            map.insert(update.project_path, update.contents);
+        }
+
+        return map;
+    }
+}
+
+fn collect_module_updates(
+    parent: &Pom,
+    parent_dir: &Path,
+    parent_project_dir: &Path,
+    old_version: &str,
+    new_version: &str,
+    updates: &mut Vec<PomUpdate>,
+) {
+    let group_id = parent
+        .get(&["project", "groupId"])
+        .or_else(|| parent.get(&["project", "parent", "groupId"]));
+    let artifact_id = parent.get(&["project", "artifactId"]);
+
+    for module in parent.get_all(&["project", "modules", "module"]) {
+        let module_pom = if module.ends_with(".xml") {
+            PathBuf::from(module)
+        } else {
+            Path::new(module).join(POM_XML_NAME)
+        };
+
+        let file = parent_dir.join(&module_pom);
+        if !file.exists() {
+            warn!("Module pom {:?} does not exist, skipping it", file);
+            continue;
+        }
+
+        let pom = Pom::parse(read_file_to_string(&file));
+        let references_parent = pom.get(&["project", "parent", "groupId"]) == group_id
+            && pom.get(&["project", "parent", "artifactId"]) == artifact_id
+            && pom.get(&["project", "parent", "version"]) == Some(old_version);
+
+        if !references_parent {
+            trace!("Module {:?} does not use {:?} as it's parent", file, artifact_id);
+            continue;
+        }
+
+        let project_path = parent_project_dir.join(&module_pom);
+        let module_dir = file.parent().unwrap().to_path_buf();
+        let module_project_dir = project_path.parent().unwrap().to_path_buf();
+
+        // A module without its own version inherits it from the parent, so any modules
+        // underneath it reference the new version too.
+        if pom.get(&["project", "version"]).is_none() {
+            collect_module_updates(&pom, &module_dir, &module_project_dir, old_version, new_version, updates);
+        }
+
+        updates.push(PomUpdate {
+            project_path: s!(project_path.to_str().unwrap()),
+            file: file,
+            contents: pom.replace(&["project", "parent", "version"], new_version).unwrap(),
+        });
+    }
+}
+
+impl Pom {
+    fn parse(text: String) -> Self {
+        let elements = scan_elements(&text);
+        return Pom { text: text, elements: elements };
+    }
+
+    fn find(&self, path: &[&str]) -> Option<&XmlElement> {
+        return self.elements.iter().find(|element| element.path == path);
+    }
+
+    fn get(&self, path: &[&str]) -> Option<&str> {
+        return self.find(path).map(|element| self.text[element.start..element.end].trim());
+    }
+
+    fn get_all(&self, path: &[&str]) -> Vec<&str> {
+        return self.elements
+            .iter()
+            .filter(|element| element.path == path)
+            .map(|element| self.text[element.start..element.end].trim())
+            .collect();
+    }
+
+    /// Swaps the text of a single element, leaving the rest of the document untouched.
+    fn replace(&self, path: &[&str], new_value: &str) -> Option<String> {
+        let element = self.find(path)?;
+        let content = &self.text[element.start..element.end];
+        let start = element.start + (content.len() - content.trim_left().len());
+        let end = element.start + content.trim_right().len();
+
+        return Some(format!("{}{}{}", &self.text[..start], new_value, &self.text[end..]));
+    }
+}
+
+/// Finds every element in the document, along with the path of element names leading to
+/// it and the range of its content. Comments, processing instructions and CDATA are skipped.
+fn scan_elements(text: &str) -> Vec<XmlElement> {
+    let mut elements = Vec::new();
+    let mut stack: Vec<(String, usize)> = Vec::new();
+    let mut pos = 0;
+
+    while let Some(offset) = text[pos..].find('<') {
+        let tag_start = pos + offset;
+        let rest = &text[tag_start..];
+
+        let skip_to = if rest.starts_with("<!--") {
+            Some("-->")
+        } else if rest.starts_with("<![CDATA[") {
+            Some("]]>")
+        } else if rest.starts_with("<?") {
+            Some("?>")
+        } else if rest.starts_with("<!") {
+            Some(">")
+        } else {
+            None
+        };
+
+        if let Some(terminator) = skip_to {
+            pos = match rest.find(terminator) {
+                Some(end) => tag_start + end + terminator.len(),
+                None => break,
+            };
+            continue;
+        }
+
+        let tag_end = match find_tag_end(text, tag_start) {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &text[tag_start + 1..tag_end];
+        pos = tag_end + 1;
+
+        if tag.starts_with('/') {
+            let name = tag[1..].trim();
+            if let Some(index) = stack.iter().rposition(|&(ref open, _)| open == name) {
+                let path: Vec<String> = stack[..index + 1].iter().map(|&(ref n, _)| n.clone()).collect();
+                elements.push(XmlElement { path: path, start: stack[index].1, end: tag_start });
+                stack.truncate(index);
+            }
+        } else if !tag.ends_with('/') {
+            let name: String = tag.chars().take_while(|c| !c.is_whitespace()).collect();
+            stack.push((name, pos));
+        }
+    }
+
+    return elements;
+}
+
+fn find_tag_end(text: &str, start: usize) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (index, c) in text[start..].char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(start + index),
+            _ => {}
+        }
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static POM: &'static str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project>
+    <!-- <version>0.0.1</version> -->
+    <parent>
+        <groupId>org.example</groupId>
+        <artifactId>parent</artifactId>
+        <version>7</version>
+    </parent>
+    <artifactId>lib</artifactId>
+    <version>1.2.3</version>
+    <dependencies>
+        <dependency>
+            <groupId>junit</groupId>
+            <artifactId>junit</artifactId>
+            <version>4.12</version>
+        </dependency>
+    </dependencies>
+</project>
+"#;
+
+    #[test]
+    fn will_find_project_version() {
+        let pom = Pom::parse(s!(POM));
+        assert_eq!(pom.get(&["project", "version"]), Some("1.2.3"));
+        assert_eq!(pom.get(&["project", "parent", "version"]), Some("7"));
+    }
+
+    #[test]
+    fn will_only_replace_project_version() {
+        let pom = Pom::parse(s!(POM));
+        let updated = pom.replace(&["project", "version"], "1.3.0").unwrap();
+
+        assert_eq!(updated, POM.replace("<version>1.2.3</version>", "<version>1.3.0</version>"));
+    }
+
+    #[test]
+    fn will_update_parent_version_in_modules() {
+        let fixture = |path: &str| format!("{}/tests/fixtures/maven-modules/{}", env!("CARGO_MANIFEST_DIR"), path);
+        let bumped = |path: &str| {
+            read_file_to_string(Path::new(&fixture(path))).replace("<version>1.0.0</version>", "<version>1.1.0</version>")
+        };
+
+        let project = MavenProject::new(s!("pom.xml"), Path::new(&fixture("pom.xml")));
+        let files = project.render_version_files(Version::parse("1.1.0").unwrap());
+
+        let mut names: Vec<&String> = files.keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["app/pom.xml", "core/nested/pom.xml", "core/pom.xml", "pom.xml"]);
+
+        assert_eq!(files["core/pom.xml"], bumped("core/pom.xml"));
+        assert_eq!(files["core/nested/pom.xml"], bumped("core/nested/pom.xml"));
+        assert_eq!(files["app/pom.xml"], bumped("app/pom.xml"));
+        assert!(files["app/pom.xml"].contains("<version>2.0.0</version>"));
+    }
+}
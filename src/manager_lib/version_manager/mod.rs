@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::vec::Vec;
+
+use ini::Ini;
+use semver::Version;
+
+use config::Config;
+use errors::{CommandError, ErrorCodes};
+use file::{read_file_to_string, write_file};
+
+use self::cargo::CargoProject;
+use self::custom::CustomProject;
+use self::git_tag::GitTagProject;
+use self::helm::HelmProject;
+use self::maven::MavenProject;
+use self::npm::NpmProject;
+use self::python::PythonProject;
+use self::version_file::VersionFileProject;
+
+mod cargo;
+mod custom;
+mod git_tag;
+mod helm;
+mod maven;
+mod npm;
+mod python;
+mod version_file;
+
+const VERSION_PROPERTIES_NAME: &'static str = "version.properties";
+const CARGO_TOML_NAME: &'static str = "Cargo.toml";
+const PACKAGE_JSON_NAME: &'static str = "package.json";
+const POM_XML_NAME: &'static str = "pom.xml";
+const PYPROJECT_TOML_NAME: &'static str = "pyproject.toml";
+const SETUP_CFG_NAME: &'static str = "setup.cfg";
+const CHART_YAML_NAME: &'static str = "Chart.yaml";
+const VERSION_FILE_NAME: &'static str = "VERSION";
+const GIT_TAG_SOURCE: &'static str = "git-tag";
+
+const PROJECT_FILES: &'static [&str] = &[
+    CARGO_TOML_NAME,
+    VERSION_PROPERTIES_NAME,
+    PACKAGE_JSON_NAME,
+    POM_XML_NAME,
+    PYPROJECT_TOML_NAME,
+    SETUP_CFG_NAME,
+    CHART_YAML_NAME,
+];
+
+/// A file that holds a version, and why it was picked.
+pub(crate) struct ProjectCandidate {
+    pub(crate) path: PathBuf,
+    pub(crate) reason: String,
+}
+
+/// Finds the project for the repo. The config decides first (`[version]` source or files,
+/// then `version-file` in `[github]`), otherwise the directories from the working directory
+/// up to the project root are searched for a single project file.
+pub(crate) fn build_project(config: &Config, project_root: &Path) -> Result<Arc<Project>, CommandError> {
+    if let Some((reason, project)) = configured_project(config, project_root)? {
+        debug!("Using project {}", reason);
+        return Ok(project);
+    }
+
+    let candidate = find_project_file(config, project_root)?;
+    debug!("Using {:?} because it {}", candidate.path, candidate.reason);
+
+    let project_path = relative_path(project_root, &candidate.path);
+    return match project_for_file(config, project_path, &candidate.path)? {
+        Some(project) => Ok(project),
+        None => Err(CommandError::new(
+            ErrorCodes::ProjectNotFound,
+            format!("{:?} is not a supported project file", candidate.path),
+        )),
+    };
+}
+
+/// The project described by the config, along with a description of the setting used.
+pub(crate) fn configured_project(
+    config: &Config,
+    project_root: &Path,
+) -> Result<Option<(String, Arc<Project>)>, CommandError> {
+    match config.version.as_ref().and_then(|x| x.source.clone()) {
+        Some(ref source) if source == GIT_TAG_SOURCE => {
+            return Ok(Some((
+                format!("`source = \"{}\"` in [version]", GIT_TAG_SOURCE),
+                Arc::new(GitTagProject::new(project_root)),
+            )));
+        }
+        Some(source) => warn!("Unknown version source `{}`, looking for a project file", source),
+        None => {}
+    }
+
+    let configured_files = config.version.as_ref().and_then(|x| x.files.clone());
+    if let Some(files) = configured_files {
+        if !files.is_empty() {
+            return Ok(Some((
+                s!("[[version.files]]"),
+                Arc::new(CustomProject::new(project_root, files)?),
+            )));
+        }
+    }
+
+    if let Some(file) = config.github.verion_file.clone() {
+        let path = project_root.join(&file);
+        if !path.exists() {
+            return Err(CommandError::new(
+                ErrorCodes::FileDoesNotExist,
+                format!("version-file `{}` does not exist", file),
+            ));
+        }
+
+        return match project_for_file(config, file.clone(), &path)? {
+            Some(project) => Ok(Some((format!("`version-file = \"{}\"` in [github]", file), project))),
+            None => Err(CommandError::new(
+                ErrorCodes::ProjectNotFound,
+                format!("version-file `{}` is not a supported project file", file),
+            )),
+        };
+    }
+
+    return Ok(None);
+}
+
+/// Walks from the working directory up to the project root, stopping at the first
+/// directory with a project file. More than one project file in that directory is
+/// an error, as there's no way to know which one holds the version.
+pub(crate) fn find_project_file(config: &Config, project_root: &Path) -> Result<ProjectCandidate, CommandError> {
+    for dir in search_path(project_root) {
+        let mut candidates = find_candidates(config, &dir);
+        trace!("Found {} project files in {:?}", candidates.len(), dir);
+
+        if candidates.len() == 1 {
+            return Ok(candidates.remove(0));
+        }
+
+        if candidates.len() > 1 {
+            let names: Vec<String> = candidates
+                .iter()
+                .map(|x| format!("{} ({})", relative_path(project_root, &x.path), x.reason))
+                .collect();
+
+            return Err(CommandError::new(
+                ErrorCodes::AmbiguousProject,
+                format!(
+                    "Found multiple project files in {:?}: {}. Set `version-file` in the [github] section of the config to pick one.",
+                    dir,
+                    names.join(", ")
+                ),
+            ));
+        }
+    }
+
+    return Err(CommandError::new(
+        ErrorCodes::ProjectNotFound,
+        format!(
+            "Unable to find a project file between {:?} and {:?}. Looked for {}.",
+            env::current_dir().unwrap(),
+            project_root,
+            project_file_names(config).join(", ")
+        ),
+    ));
+}
+
+/// The directories to search, from the working directory up to (and including) the
+/// project root.
+pub(crate) fn search_path(project_root: &Path) -> Vec<PathBuf> {
+    let mut path = env::current_dir().unwrap();
+    let mut dirs = Vec::new();
+
+    loop {
+        dirs.push(path.clone());
+        if path == project_root || !path.starts_with(project_root) {
+            break;
+        }
+
+        match path.clone().parent() {
+            Some(parent) => path = parent.to_path_buf(),
+            None => break,
+        }
+    }
+
+    return dirs;
+}
+
+pub(crate) fn find_candidates(config: &Config, dir: &Path) -> Vec<ProjectCandidate> {
+    return project_file_names(config)
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            describe_project_file(config, &path).map(|reason| ProjectCandidate {
+                path: path,
+                reason: reason,
+            })
+        })
+        .collect();
+}
+
+/// The name of the plain version file, `VERSION` unless `file-name` is set in [version].
+fn version_file_name(config: &Config) -> String {
+    return config
+        .version
+        .as_ref()
+        .and_then(|x| x.file_name.clone())
+        .unwrap_or(s!(VERSION_FILE_NAME));
+}
+
+fn project_file_names(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = PROJECT_FILES.iter().map(|x| s!(*x)).collect();
+    names.push(version_file_name(config));
+
+    return names;
+}
+
+/// Checks that a project file actually holds a version, returning why it can be used.
+fn describe_project_file(config: &Config, path: &Path) -> Option<String> {
+    let file_name = path.file_name().and_then(|x| x.to_str()).unwrap_or("");
+
+    return match file_name {
+        name if name == version_file_name(config) => version_file::detect(path),
+        CARGO_TOML_NAME => cargo::detect(path),
+        VERSION_PROPERTIES_NAME => Some(s!("is a version.properties file")),
+        PACKAGE_JSON_NAME => npm::detect(path),
+        POM_XML_NAME => Some(s!("is a Maven pom.xml")),
+        PYPROJECT_TOML_NAME => python::detect(path),
+        SETUP_CFG_NAME if !path.with_file_name(PYPROJECT_TOML_NAME).exists() => python::detect(path),
+        CHART_YAML_NAME => Some(s!("is a Helm chart")),
+        _ => None,
+    };
+}
+
+/// The project for a supported project file, or an error when the file is one that can't
+/// be used.
+fn project_for_file(config: &Config, project_path: String, path: &Path) -> Result<Option<Arc<Project>>, CommandError> {
+    trace!("Looking for project file at {:?}", path);
+
+    let file_name = path.file_name().and_then(|x| x.to_str()).unwrap_or("");
+
+    return Ok(match file_name {
+        name if name == version_file_name(config) => Some(Arc::new(VersionFileProject::new(project_path, path))),
+        CARGO_TOML_NAME => Some(Arc::new(CargoProject::new(project_path, path, config.cargo.as_ref())?)),
+        VERSION_PROPERTIES_NAME => Some(Arc::new(VersionPropertiesProject::new(project_path, path))),
+        PACKAGE_JSON_NAME => Some(Arc::new(NpmProject::new(project_path, path))),
+        POM_XML_NAME => Some(Arc::new(MavenProject::new(project_path, path))),
+        PYPROJECT_TOML_NAME | SETUP_CFG_NAME => Some(Arc::new(PythonProject::new(project_path, path))),
+        CHART_YAML_NAME => Some(Arc::new(HelmProject::new(project_path, path, config.helm.as_ref()))),
+        _ => None,
+    });
+}
+
+fn relative_path(project_root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(project_root).unwrap_or(path);
+    return s!(relative.to_str().unwrap());
+}
+
+pub(crate) trait Project {
+    fn get_project_type(&self) -> &'static str;
+    fn get_version(&self) -> Version;
+    fn update_version(&self, Version);
+    fn render_version_files(&self, Version) -> HashMap<String, String>;
+    fn get_version_files(&self) -> Vec<PathBuf>;
+}
+
+struct VersionPropertiesProject {
+    project_path: String,
+    version_file: String,
+}
+
+impl VersionPropertiesProject {
+    fn new(project_path: String, path: &Path) -> Self {
+        debug!("Project path: {:?}", path);
+        return VersionPropertiesProject {
+            project_path: project_path, version_file: s!(path.to_str().unwrap()),
+        };
+    }
+
+    fn get_version_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using version.properties located at {:?}", self.version_file);
+        return path_buf;
+    }
+
+    fn read_version_file(&self) -> String {
+        let version_file = self.get_version_file();
+        let version_path = version_file.as_path();
+        return read_file_to_string(version_path);
+    }
+
+    fn ini_to_string(conf: Ini) -> String {
+        let mut version_file_buffer = Vec::new();
+        conf.write_to(&mut version_file_buffer).unwrap();
+
+        return String::from_utf8(version_file_buffer).expect("Ini File to be UTF-8");
+    }
+}
+
+impl Project for VersionPropertiesProject {
+    fn get_project_type(&self) -> &'static str {
+        return "version-properties";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return vec![self.get_version_file()];
+    }
+
+    fn get_version(&self) -> Version {
+        let conf: Ini = Ini::load_from_str(&self.read_version_file()).unwrap();
+
+        let version_string = conf.section(None::<String>)
+            .unwrap()
+            .get("version")
+            .unwrap();
+        debug!("Current project version: {}", version_string);
+
+        return Version::parse(version_string).unwrap();
+    }
+
+    fn update_version(&self, version: Version) {
+        let mut conf: Ini = Ini::load_from_str(&self.read_version_file()).unwrap();
+
+        conf.with_section(None::<String>)
+            .set("version", version.to_string());
+
+        let body = VersionPropertiesProject::ini_to_string(conf);
+
+        write_file(body, &self.get_version_file());
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut conf: Ini = Ini::load_from_str(&self.read_version_file()).unwrap();
+        conf.with_section(None::<String>)
+            .set("version", version.to_string());
+        let version_text = VersionPropertiesProject::ini_to_string(conf);
+
+        let mut map = HashMap::new();
+        map.insert(self.project_path.clone(), version_text);
+
+        return map;
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use json;
+use semver::Version;
+
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+const PACKAGE_LOCK_NAME: &'static str = "package-lock.json";
+
+pub(crate) struct NpmProject {
+    project_path: String,
+    version_file: String,
+}
+
+impl NpmProject {
+    pub(crate) fn new(project_path: String, path: &Path) -> Self {
+        debug!("Project path: {:?}", path);
+        return NpmProject {
+            project_path: project_path, version_file: s!(path.to_str().unwrap()),
+        };
+    }
+
+    fn get_package_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using package.json located at {:?}", path_buf);
+        return path_buf;
+    }
+
+    fn get_lock_file(&self) -> Option<PathBuf> {
+        let lock_file = self.get_package_file().with_file_name(PACKAGE_LOCK_NAME);
+
+        return if lock_file.exists() {
+            trace!("Using package-lock.json located at {:?}", lock_file);
+            Some(lock_file)
+        } else {
+            None
+        };
+    }
+
+    fn get_lock_project_path(&self) -> String {
+        let lock_path = Path::new(&self.project_path).with_file_name(PACKAGE_LOCK_NAME);
+        return s!(lock_path.to_str().unwrap());
+    }
+}
+
+impl Project for NpmProject {
+    fn get_project_type(&self) -> &'static str {
+        return "npm";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.get_package_file()];
+        if let Some(lock_file) = self.get_lock_file() {
+            files.push(lock_file);
+        }
+
+        return files;
+    }
+
+    fn get_version(&self) -> Version {
+        let package_path = self.get_package_file();
+        let package_path = package_path.as_path();
+
+        let parsed = json::parse(&read_file_to_string(package_path)).unwrap();
+
+        let version = match parsed["version"].as_str() {
+            Some(value) => s!(value),
+            None => panic!("Unable to get version for package.json located at {:?}", package_path),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return Version::parse(&version).unwrap();
+    }
+
+    fn update_version(&self, version: Version) {
+        let package_path = self.get_package_file();
+        let text = update_package_version(&read_file_to_string(&package_path), &version);
+        write_file(text, &package_path);
+
+        if let Some(lock_path) = self.get_lock_file() {
+            let text = update_lock_version(&read_file_to_string(&lock_path), &version);
+            write_file(text, &lock_path);
+        }
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+
+        let package_text = read_file_to_string(&self.get_package_file());
+        map.insert(self.project_path.clone(), update_package_version(&package_text, &version));
+
+        if let Some(lock_path) = self.get_lock_file() {
+            let lock_text = read_file_to_string(&lock_path);
+            map.insert(self.get_lock_project_path(), update_lock_version(&lock_text, &version));
+        }
+
+        return map;
+    }
+}
+
+pub(crate) fn detect(path: &Path) -> Option<String> {
+    return match json::parse(&read_file_to_string(path)) {
+        Ok(ref parsed) if parsed["version"].is_string() => Some(s!("has a top level version")),
+        _ => None,
+    };
+}
+
+fn update_package_version(text: &str, version: &Version) -> String {
+    return match replace_string_value(text, &["version"], &version.to_string()) {
+        Some(updated) => updated,
+        None => panic!("package.json does not have a top level version"),
+    };
+}
+
+/// package-lock.json keeps the root version at the top level, and from lockfileVersion 2
+/// onwards again under `packages[""]`. Both are updated when present.
+fn update_lock_version(text: &str, version: &Version) -> String {
+    let version = version.to_string();
+    let mut text = s!(text);
+
+    for path in [vec!["version"], vec!["packages", "", "version"]].iter() {
+        if let Some(updated) = replace_string_value(&text, path, &version) {
+            text = updated;
+        }
+    }
+
+    return text;
+}
+
+/// Replaces the string found by following `path` through nested objects, leaving every
+/// other byte of the document alone so key order and formatting are kept.
+fn replace_string_value(text: &str, path: &[&str], new_value: &str) -> Option<String> {
+    let (start, end) = find_value(text, path)?;
+    if text.as_bytes()[start] != b'"' {
+        return None;
+    }
+
+    return Some(format!("{}\"{}\"{}", &text[..start], new_value, &text[end..]));
+}
+
+fn find_value(text: &str, path: &[&str]) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut range = {
+        let start = skip_whitespace(bytes, 0);
+        (start, skip_value(bytes, start)?)
+    };
+
+    for key in path {
+        if bytes.get(range.0) != Some(&b'{') {
+            return None;
+        }
+        range = find_member(text, range.0, key)?;
+    }
+
+    return Some(range);
+}
+
+fn find_member(text: &str, object_start: usize, key: &str) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut pos = skip_whitespace(bytes, object_start + 1);
+
+    while bytes.get(pos) == Some(&b'"') {
+        let key_end = skip_string(bytes, pos)?;
+        let name = &text[pos + 1..key_end - 1];
+
+        pos = skip_whitespace(bytes, key_end);
+        if bytes.get(pos) != Some(&b':') {
+            return None;
+        }
+
+        let value_start = skip_whitespace(bytes, pos + 1);
+        let value_end = skip_value(bytes, value_start)?;
+        if name == key {
+            return Some((value_start, value_end));
+        }
+
+        pos = skip_whitespace(bytes, value_end);
+        if bytes.get(pos) != Some(&b',') {
+            return None;
+        }
+        pos = skip_whitespace(bytes, pos + 1);
+    }
+
+    return None;
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && (bytes[pos] as char).is_whitespace() {
+        pos += 1;
+    }
+
+    return pos;
+}
+
+fn skip_string(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut pos = start + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+
+    return None;
+}
+
+fn skip_value(bytes: &[u8], start: usize) -> Option<usize> {
+    return match bytes.get(start) {
+        Some(&b'"') => skip_string(bytes, start),
+        Some(&b'{') | Some(&b'[') => {
+            let mut depth = 0;
+            let mut pos = start;
+            while pos < bytes.len() {
+                match bytes[pos] {
+                    b'"' => {
+                        pos = skip_string(bytes, pos)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(pos + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+            None
+        }
+        Some(_) => {
+            let mut pos = start;
+            while pos < bytes.len() && !b",}] \t\r\n".contains(&bytes[pos]) {
+                pos += 1;
+            }
+            Some(pos)
+        }
+        None => None,
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn will_only_update_top_level_version() {
+        let text = "{\n  \"name\": \"foo\",\n  \"dependencies\": {\n    \"version\": \"^1.0.0\"\n  },\n  \"version\": \"1.2.3\",\n  \"main\": \"index.js\"\n}\n";
+        let version = Version::parse("1.3.0").unwrap();
+
+        assert_eq!(
+            update_package_version(text, &version),
+            "{\n  \"name\": \"foo\",\n  \"dependencies\": {\n    \"version\": \"^1.0.0\"\n  },\n  \"version\": \"1.3.0\",\n  \"main\": \"index.js\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn will_update_both_lock_file_versions() {
+        let text = "{\"name\":\"foo\",\"version\":\"1.2.3\",\"lockfileVersion\":2,\"packages\":{\"\":{\"name\":\"foo\",\"version\":\"1.2.3\"},\"node_modules/bar\":{\"version\":\"1.2.3\"}}}";
+        let version = Version::parse("2.0.0").unwrap();
+
+        assert_eq!(
+            update_lock_version(text, &version),
+            "{\"name\":\"foo\",\"version\":\"2.0.0\",\"lockfileVersion\":2,\"packages\":{\"\":{\"name\":\"foo\",\"version\":\"2.0.0\"},\"node_modules/bar\":{\"version\":\"1.2.3\"}}}"
+        );
+    }
+}
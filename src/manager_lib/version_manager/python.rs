@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use semver::Version;
+use toml;
+use toml_edit::{value, Document};
+
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+const PYPROJECT_TOML_NAME: &'static str = "pyproject.toml";
+const SETUP_CFG_NAME: &'static str = "setup.cfg";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VersionLocation {
+    /// PEP 621 `[project].version` in pyproject.toml
+    Project,
+    /// `[tool.poetry].version` in pyproject.toml
+    Poetry,
+    /// `version` in the `[metadata]` section of setup.cfg
+    SetupCfg,
+}
+
+pub(crate) struct PythonProject {
+    project_path: String,
+    version_file: String,
+    location: VersionLocation,
+}
+
+impl PythonProject {
+    /// Works out which file actually holds the version. A pyproject.toml that does not
+    /// declare a version (or declares it as dynamic) falls back to setup.cfg next to it.
+    pub(crate) fn new(project_path: String, path: &Path) -> Self {
+        debug!("Project path: {:?}", path);
+
+        let file_name = path.file_name().and_then(|x| x.to_str());
+        if file_name == Some(PYPROJECT_TOML_NAME) {
+            let parsed: toml::value::Value = toml::from_str(&read_file_to_string(path)).unwrap();
+
+            if parsed.get("project").and_then(|x| x.get("version")).is_some() {
+                return PythonProject::at(project_path, path, VersionLocation::Project);
+            }
+
+            if parsed.get("tool").and_then(|x| x.get("poetry")).and_then(|x| x.get("version")).is_some() {
+                return PythonProject::at(project_path, path, VersionLocation::Poetry);
+            }
+
+            let setup_cfg = path.with_file_name(SETUP_CFG_NAME);
+            if setup_cfg.exists() {
+                let setup_cfg_project_path = Path::new(&project_path).with_file_name(SETUP_CFG_NAME);
+                return PythonProject::at(
+                    s!(setup_cfg_project_path.to_str().unwrap()),
+                    &setup_cfg,
+                    VersionLocation::SetupCfg,
+                );
+            }
+
+            panic!("Unable to find a version in {:?}, [project] or [tool.poetry] need a version", path);
+        }
+
+        return PythonProject::at(project_path, path, VersionLocation::SetupCfg);
+    }
+
+    fn at(project_path: String, path: &Path, location: VersionLocation) -> Self {
+        trace!("Python version is stored as {:?} in {:?}", location, path);
+        return PythonProject {
+            project_path: project_path,
+            version_file: s!(path.to_str().unwrap()),
+            location: location,
+        };
+    }
+
+    fn get_version_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using {:?} located at {:?}", self.location, path_buf);
+        return path_buf;
+    }
+
+    fn render_version_file(&self, version: &Version) -> String {
+        let version_path = self.get_version_file();
+        let text = read_file_to_string(&version_path);
+
+        return match self.location {
+            VersionLocation::Project => {
+                let mut doc = text.parse::<Document>().expect("invalid doc");
+                doc["project"]["version"] = value(version.to_string());
+                doc.to_string()
+            }
+            VersionLocation::Poetry => {
+                let mut doc = text.parse::<Document>().expect("invalid doc");
+                doc["tool"]["poetry"]["version"] = value(version.to_string());
+                doc.to_string()
+            }
+            VersionLocation::SetupCfg => match find_setup_cfg_version(&text) {
+                Some((start, end)) => format!("{}{}{}", &text[..start], version, &text[end..]),
+                None => panic!("Unable to get version for setup.cfg located at {:?}", version_path),
+            },
+        };
+    }
+}
+
+impl Project for PythonProject {
+    fn get_project_type(&self) -> &'static str {
+        return "python";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return vec![self.get_version_file()];
+    }
+
+    fn get_version(&self) -> Version {
+        let version_path = self.get_version_file();
+        let version_path = version_path.as_path();
+        let text = read_file_to_string(version_path);
+
+        let version = match self.location {
+            VersionLocation::SetupCfg => find_setup_cfg_version(&text).map(|(start, end)| s!(&text[start..end])),
+            location => {
+                let parsed: toml::value::Value = toml::from_str(&text).unwrap();
+                let table = if location == VersionLocation::Project {
+                    parsed.get("project")
+                } else {
+                    parsed.get("tool").and_then(|x| x.get("poetry"))
+                };
+
+                table
+                    .and_then(|x| x.get("version"))
+                    .and_then(|x| x.as_str())
+                    .map(|x| s!(x))
+            }
+        };
+
+        let version = match version {
+            Some(value) => value,
+            None => panic!("Unable to get version for {:?}", version_path),
+        };
+
+        debug!("Current project version: {}", version);
+
+        return match Version::parse(&version) {
+            Ok(version) => version,
+            Err(_) => panic!("Version `{}` in {:?} is not a semver version", version, version_path),
+        };
+    }
+
+    fn update_version(&self, version: Version) {
+        let text = self.render_version_file(&version);
+        write_file(text, &self.get_version_file());
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert(self.project_path.clone(), self.render_version_file(&version));
+
+        return map;
+    }
+}
+
+pub(crate) fn detect(path: &Path) -> Option<String> {
+    let text = read_file_to_string(path);
+
+    if path.file_name().and_then(|x| x.to_str()) == Some(SETUP_CFG_NAME) {
+        return find_setup_cfg_version(&text).map(|_| s!("has a [metadata] version"));
+    }
+
+    let parsed: toml::value::Value = match toml::from_str(&text) {
+        Ok(parsed) => parsed,
+        Err(_) => return None,
+    };
+
+    if parsed.get("project").and_then(|x| x.get("version")).is_some() {
+        return Some(s!("has a [project] version"));
+    }
+
+    if parsed.get("tool").and_then(|x| x.get("poetry")).and_then(|x| x.get("version")).is_some() {
+        return Some(s!("has a [tool.poetry] version"));
+    }
+
+    let setup_cfg = path.with_file_name(SETUP_CFG_NAME);
+    if setup_cfg.exists() && find_setup_cfg_version(&read_file_to_string(&setup_cfg)).is_some() {
+        return Some(s!("uses the [metadata] version in setup.cfg"));
+    }
+
+    return None;
+}
+
+/// Finds the byte range of the `version` value in the `[metadata]` section of a setup.cfg
+/// so it can be swapped without rewriting the rest of the file.
+fn find_setup_cfg_version(text: &str) -> Option<(usize, usize)> {
+    let mut in_metadata = false;
+    let mut line_start = 0;
+
+    for line in text.split('\n') {
+        let offset = line_start;
+        line_start += line.len() + 1;
+
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_metadata = trimmed == "[metadata]";
+            continue;
+        }
+
+        if !in_metadata || line.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let separator = match line.find(|c| c == '=' || c == ':') {
+            Some(index) => index,
+            None => continue,
+        };
+
+        if line[..separator].trim() != "version" {
+            continue;
+        }
+
+        let value = &line[separator + 1..];
+        let value_start = offset + separator + 1 + (value.len() - value.trim_left().len());
+        let value_end = offset + separator + 1 + value.trim_right().len();
+
+        return Some((value_start, value_end));
+    }
+
+    return None;
+}
+
+#[test]
+fn will_find_setup_cfg_version() {
+    let text = "[options]\nversion = 0.0.1\n\n[metadata]\nname = foo\nversion = 1.2.3\r\nauthor = bar\n";
+    let (start, end) = find_setup_cfg_version(text).unwrap();
+
+    assert_eq!(&text[start..end], "1.2.3");
+}
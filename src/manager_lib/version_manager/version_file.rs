@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use semver::Version;
+
+use file::{read_file_to_string, write_file};
+use super::Project;
+
+/// A file that only holds the version, like the `VERSION` files used by Go and shell
+/// projects. A leading `v` and any trailing newline are kept when the version is updated.
+pub(crate) struct VersionFileProject {
+    project_path: String,
+    version_file: String,
+}
+
+impl VersionFileProject {
+    pub(crate) fn new(project_path: String, path: &Path) -> Self {
+        debug!("Project path: {:?}", path);
+        return VersionFileProject {
+            project_path: project_path,
+            version_file: s!(path.to_str().unwrap()),
+        };
+    }
+
+    fn get_version_file(&self) -> PathBuf {
+        let path_buf = PathBuf::from(self.version_file.clone());
+
+        trace!("Using version file located at {:?}", path_buf);
+        return path_buf;
+    }
+
+    fn render_version_file(&self, version: &Version) -> String {
+        let text = read_file_to_string(&self.get_version_file());
+        let (start, end) = find_version(&text);
+
+        return format!("{}{}{}", &text[..start], version, &text[end..]);
+    }
+}
+
+impl Project for VersionFileProject {
+    fn get_project_type(&self) -> &'static str {
+        return "version-file";
+    }
+
+    fn get_version_files(&self) -> Vec<PathBuf> {
+        return vec![self.get_version_file()];
+    }
+
+    fn get_version(&self) -> Version {
+        let version_path = self.get_version_file();
+        let text = read_file_to_string(&version_path);
+        let (start, end) = find_version(&text);
+
+        let version = &text[start..end];
+        debug!("Current project version: {}", version);
+
+        return match Version::parse(version) {
+            Ok(version) => version,
+            Err(_) => panic!("Version `{}` in {:?} is not a semver version", version, version_path),
+        };
+    }
+
+    fn update_version(&self, version: Version) {
+        let text = self.render_version_file(&version);
+        write_file(text, &self.get_version_file());
+    }
+
+    fn render_version_files(&self, version: Version) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert(self.project_path.clone(), self.render_version_file(&version));
+
+        return map;
+    }
+}
+
+pub(crate) fn detect(path: &Path) -> Option<String> {
+    let text = read_file_to_string(path);
+    let (start, end) = find_version(&text);
+
+    return match Version::parse(&text[start..end]) {
+        Ok(_) => Some(s!("holds a bare version")),
+        Err(_) => None,
+    };
+}
+
+/// The byte range of the version, skipping surrounding whitespace and a leading `v`.
+fn find_version(text: &str) -> (usize, usize) {
+    let start = text.len() - text.trim_left().len();
+    let end = start + text.trim().len();
+
+    if text[start..end].starts_with('v') || text[start..end].starts_with('V') {
+        return (start + 1, end);
+    }
+
+    return (start, end);
+}
+
+#[test]
+fn will_keep_prefix_and_newline() {
+    let text = "v1.2.3\n";
+    let (start, end) = find_version(text);
+
+    assert_eq!(&text[start..end], "1.2.3");
+    assert_eq!(format!("{}{}{}", &text[..start], "1.3.0", &text[end..]), "v1.3.0\n");
+}